@@ -8,8 +8,12 @@ use std::{
 use volezk::{
     block::*,
     ot::co15::{CO15Receiver, CO15Sender},
-    ot::extension::iknp::{ot_ext_receive, ot_ext_send},
-    Channel,
+    ot::extension::bit_matrix::BitMatrix,
+    ot::extension::iknp::{
+        ot_ext_receive, ot_ext_receive_vec, ot_ext_send, ot_ext_send_vec, SecurityLevel,
+    },
+    ot::extension::softspoken,
+    AbstractChannel, Channel, CommStats, MemChannel, TrackChannel,
 };
 
 const M: usize = 1000;
@@ -75,8 +79,150 @@ fn iknp() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+fn iknp_vec(m: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let (ot_sender_stream, ot_receiver_stream) = UnixStream::pair().unwrap();
+    let (ext_sender_stream, ext_receiver_stream) = UnixStream::pair().unwrap();
+    let choices = (0..m).map(|i| i % 3 == 0).collect::<Vec<_>>();
+
+    let receiver_handle = thread::spawn(move || {
+        let mut rng = thread_rng();
+        let reader = BufReader::new(ot_sender_stream.try_clone().unwrap());
+        let writer = BufWriter::new(ot_sender_stream);
+        let mut ot_sender = CO15Sender::setup(Channel::new(reader, writer), &mut rng).unwrap();
+
+        let reader = BufReader::new(ext_receiver_stream.try_clone().unwrap());
+        let writer = BufWriter::new(ext_receiver_stream);
+        let mut ext_receiver_chan = Channel::new(reader, writer);
+
+        ot_ext_receive_vec::<_, Block128, _>(
+            &mut ot_sender,
+            &choices,
+            &mut ext_receiver_chan,
+            SecurityLevel::default(),
+        )
+    });
+
+    let reader = BufReader::new(ot_receiver_stream.try_clone().unwrap());
+    let writer = BufWriter::new(ot_receiver_stream);
+    let mut ot_receiver = CO15Receiver::setup(Channel::new(reader, writer)).unwrap();
+
+    let values = vec![[Block128::from(1), Block128::from(2)]; m];
+    let reader = BufReader::new(ext_sender_stream.try_clone().unwrap());
+    let writer = BufWriter::new(ext_sender_stream);
+    let mut ext_sender_chan = Channel::new(reader, writer);
+
+    ot_ext_send_vec(
+        &mut ot_receiver,
+        &values,
+        &mut ext_sender_chan,
+        SecurityLevel::default(),
+    )?;
+
+    let receiver_result = receiver_handle.join().unwrap();
+    assert!(receiver_result.is_ok());
+
+    Ok(())
+}
+
+fn softspoken_vec(m: usize, k: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let (ot_sender_stream, ot_receiver_stream) = UnixStream::pair().unwrap();
+    let (ext_sender_stream, ext_receiver_stream) = UnixStream::pair().unwrap();
+    let choices = (0..m).map(|i| i % 3 == 0).collect::<Vec<_>>();
+
+    let receiver_handle = thread::spawn(move || {
+        let mut rng = thread_rng();
+        let reader = BufReader::new(ot_sender_stream.try_clone().unwrap());
+        let writer = BufWriter::new(ot_sender_stream);
+        let mut ot_sender = CO15Sender::setup(Channel::new(reader, writer), &mut rng).unwrap();
+
+        let reader = BufReader::new(ext_receiver_stream.try_clone().unwrap());
+        let writer = BufWriter::new(ext_receiver_stream);
+        let mut ext_receiver_chan = Channel::new(reader, writer);
+
+        softspoken::ot_ext_receive_vec::<_, Block128, _>(
+            &mut ot_sender,
+            &choices,
+            &mut ext_receiver_chan,
+            k,
+        )
+    });
+
+    let reader = BufReader::new(ot_receiver_stream.try_clone().unwrap());
+    let writer = BufWriter::new(ot_receiver_stream);
+    let mut ot_receiver = CO15Receiver::setup(Channel::new(reader, writer)).unwrap();
+
+    let values = vec![[Block128::from(1), Block128::from(2)]; m];
+    let reader = BufReader::new(ext_sender_stream.try_clone().unwrap());
+    let writer = BufWriter::new(ext_sender_stream);
+    let mut ext_sender_chan = Channel::new(reader, writer);
+
+    softspoken::ot_ext_send_vec(&mut ot_receiver, &values, &mut ext_sender_chan, k)?;
+
+    let receiver_result = receiver_handle.join().unwrap();
+    assert!(receiver_result.is_ok());
+
+    Ok(())
+}
+
+/// Communication of the IKNP sender, including the base OTs
+fn iknp_vec_communication(m: usize) -> Result<CommStats, Box<dyn std::error::Error>> {
+    let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+    let (ext_sender_channel, mut ext_receiver_channel) = MemChannel::pair();
+    let choices = (0..m).map(|i| i % 3 == 0).collect::<Vec<_>>();
+
+    let receiver_handle = thread::spawn(move || {
+        let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut thread_rng())?;
+        ot_ext_receive_vec::<_, Block128, _>(
+            &mut ot_sender,
+            &choices,
+            &mut ext_receiver_channel,
+            SecurityLevel::default(),
+        )
+    });
+
+    let tracker = TrackChannel::new(ot_receiver_channel);
+    tracker.set_phase("base-ot");
+    let mut ot_receiver = CO15Receiver::setup(tracker.clone())?;
+    let mut ext_sender_channel = TrackChannel::new(ext_sender_channel);
+    ext_sender_channel.set_phase("extension");
+
+    let values = vec![[Block128::from(1), Block128::from(2)]; m];
+    ot_ext_send_vec(
+        &mut ot_receiver,
+        &values,
+        &mut ext_sender_channel,
+        SecurityLevel::default(),
+    )?;
+    receiver_handle.join().unwrap()?;
+
+    let mut stats = tracker.snapshot();
+    stats.merge(&ext_sender_channel.snapshot());
+    Ok(stats)
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
+    match iknp_vec_communication(100_000) {
+        Ok(stats) => println!("Communication of IKNP vec sender: K=128, M=100000\n{stats}"),
+        Err(e) => eprintln!("Failed to measure the communication of IKNP vec sender: {e}"),
+    }
+
     c.bench_function("OT Extension IKNP: K=128, M=1000", |b| b.iter(iknp));
+
+    c.bench_function("OT Extension IKNP vec: K=128, M=100000", |b| {
+        b.iter(|| iknp_vec(100_000))
+    });
+
+    for k in [2, 4, 8] {
+        c.bench_function(
+            &format!("OT Extension SoftSpokenOT: k={k}, M=100000"),
+            |b| b.iter(|| softspoken_vec(100_000, k)),
+        );
+    }
+
+    let matrix = BitMatrix::random(128, 1 << 20, &mut thread_rng());
+    c.bench_function("BitMatrix transpose: 128 x 2^20", |b| {
+        b.iter(|| matrix.transpose())
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -1,12 +1,10 @@
 use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
 use aes::Aes256;
 use std::default::Default;
+use std::ops::BitXor;
 
 use crate::types::IsZero;
 
-#[derive(thiserror::Error, Debug)]
-enum BlockError {}
-
 pub trait Block {
     // Encrypt in-place using AES256
     fn encrypt(&self, key: &[u8; 32]) -> Self;
@@ -32,7 +30,7 @@ impl Block for Block128 {
         let mut g_val = GenericArray::from(self.0);
         cipher.encrypt_block(&mut g_val);
 
-        Self(g_val.try_into().unwrap())
+        Self(g_val.into())
     }
 
     fn decrypt(&self, key: &[u8; 32]) -> Self {
@@ -42,7 +40,7 @@ impl Block for Block128 {
         let mut g_val = GenericArray::from(self.0);
         cipher.decrypt_block(&mut g_val);
 
-        Self(g_val.try_into().unwrap())
+        Self(g_val.into())
     }
 
     fn as_bytes(&self) -> Vec<u8> {
@@ -59,7 +57,7 @@ impl Block for Block128 {
             dst[..bytes.len()].copy_from_slice(bytes);
             Self(dst)
         } else {
-            Self(bytes[0..16].try_into().unwrap())
+            Self(std::array::from_fn(|i| bytes[i]))
         }
     }
 
@@ -86,6 +84,42 @@ impl From<[u8; 16]> for Block128 {
     }
 }
 
+impl From<Block128> for u128 {
+    fn from(value: Block128) -> Self {
+        u128::from_be_bytes(value.0)
+    }
+}
+
+impl BitXor for Block128 {
+    type Output = Self;
+
+    fn bitxor(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] ^ rhs.0[i]))
+    }
+}
+
+impl Block128 {
+    /// Multiplication over GF(2^128) with the reduction polynomial x^128 + x^7 + x^2 + x + 1.
+    /// i-th bit of the u128 representation is the coefficient of x^i.
+    pub fn gf_mul(&self, rhs: &Self) -> Self {
+        let mut a = u128::from(*self);
+        let mut b = u128::from(*rhs);
+        let mut res = 0u128;
+        while b != 0 {
+            if b & 1 == 1 {
+                res ^= a;
+            }
+            b >>= 1;
+            let carry = a >> 127;
+            a <<= 1;
+            if carry == 1 {
+                a ^= 0x87;
+            }
+        }
+        Self::from(res)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub struct Block256([Block128; 2]);
 
@@ -146,41 +180,33 @@ impl From<[Block128; 2]> for Block256 {
 
 impl From<[u8; 32]> for Block256 {
     fn from(value: [u8; 32]) -> Self {
-        let v0: [u8; 16] = value[0..16].try_into().unwrap();
-        let v1: [u8; 16] = value[16..32].try_into().unwrap();
-        Self([Block128::from(v0), Block128::from(v1)])
+        Self(std::array::from_fn(|k| {
+            Block128::from(std::array::from_fn(|i| value[16 * k + i]))
+        }))
     }
 }
 
 // Impl arbitrary length Block
 impl<const L: usize> Block for [Block128; L] {
     fn encrypt(&self, key: &[u8; 32]) -> Self {
-        self.iter()
-            .map(|b| b.encrypt(key))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+        self.map(|b| b.encrypt(key))
     }
 
     fn decrypt(&self, key: &[u8; 32]) -> Self {
-        self.iter()
-            .map(|b| b.decrypt(key))
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+        self.map(|b| b.decrypt(key))
     }
 
     fn as_bytes(&self) -> Vec<u8> {
         self.iter().flat_map(|b| b.as_bytes()).collect::<Vec<_>>()
     }
 
+    /// Missing bytes are padded with zeros, and extra bytes are ignored.
     fn from_bytes(bytes: &[u8]) -> Self {
-        bytes
-            .chunks(16)
-            .map(Block128::from_bytes)
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
+        std::array::from_fn(|i| {
+            let start = (i * 16).min(bytes.len());
+            let end = ((i + 1) * 16).min(bytes.len());
+            Block128::from_bytes(&bytes[start..end])
+        })
     }
 
     fn bytes_len(&self) -> usize {
@@ -196,7 +222,7 @@ mod tests {
     fn gen_key() -> [u8; 32] {
         let mut hasher = Keccak256::default();
         hasher.update(b"Key");
-        hasher.finalize().try_into().unwrap()
+        hasher.finalize().into()
     }
 
     #[test]
@@ -229,6 +255,23 @@ mod tests {
         assert_eq!(decrypted, block);
     }
 
+    #[test]
+    fn test_block128_gf_mul() {
+        let a = Block128::from(0x1234_5678_9abc_def0_1234_5678_9abc_def0);
+        let b = Block128::from(0xdead_beef);
+        let c = Block128::from(1 << 100);
+
+        assert_eq!(a.gf_mul(&Block128::from(1)), a);
+        assert_eq!(a.gf_mul(&b), b.gf_mul(&a));
+        // distributive over xor
+        assert_eq!(a.gf_mul(&(b ^ c)), a.gf_mul(&b) ^ a.gf_mul(&c));
+        // x^127 * x = x^128 = x^7 + x^2 + x + 1
+        assert_eq!(
+            Block128::from(1 << 127).gf_mul(&Block128::from(2)),
+            Block128::from(0x87)
+        );
+    }
+
     #[test]
     fn test_block_vec() {
         let blocks = [
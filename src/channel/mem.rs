@@ -0,0 +1,130 @@
+//! Channel between two parties in the same process, backed by in-memory queues.
+use std::{
+    io::{Error as IoError, ErrorKind, Read, Write},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use super::SyncChannel;
+
+/// Reading half of `MemChannel`
+pub struct MemReader {
+    receiver: Receiver<Vec<u8>>,
+    // message received but not read yet
+    pending: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for MemReader {
+    /// Block until the peer flushes. Returns end of file once the peer is dropped.
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.pending.len() {
+            match self.receiver.recv() {
+                Ok(message) => {
+                    self.pending = message;
+                    self.pos = 0;
+                }
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.pending.len() - self.pos);
+        buf[..n].copy_from_slice(&self.pending[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Writing half of `MemChannel`.
+/// Written bytes are buffered and delivered to the peer on flush, like `BufWriter`.
+pub struct MemWriter {
+    sender: Sender<Vec<u8>>,
+    buffer: Vec<u8>,
+}
+
+impl Write for MemWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.sender
+            .send(std::mem::take(&mut self.buffer))
+            .map_err(|_| IoError::from(ErrorKind::BrokenPipe))
+    }
+}
+
+impl Drop for MemWriter {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// In-memory channel, which is `Send` so that each party can run in its own thread
+pub type MemChannel = SyncChannel<MemReader, MemWriter>;
+
+impl MemChannel {
+    /// Create two connected channels
+    pub fn pair() -> (Self, Self) {
+        let (sender_a, receiver_a) = channel();
+        let (sender_b, receiver_b) = channel();
+        let half = |sender, receiver| {
+            SyncChannel::new(
+                MemReader {
+                    receiver,
+                    pending: Vec::new(),
+                    pos: 0,
+                },
+                MemWriter {
+                    sender,
+                    buffer: Vec::new(),
+                },
+            )
+        };
+        (half(sender_a, receiver_b), half(sender_b, receiver_a))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channel::{AbstractChannel, ChannelError},
+        ot::OTError,
+    };
+
+    #[test]
+    fn test_mem_channel() -> Result<(), ChannelError> {
+        let (mut a, mut b) = MemChannel::pair();
+
+        // both parties simulated in one thread
+        a.write_bytes(&[1, 2, 3])?;
+        a.write_bytes(&[4])?;
+        a.flush()?;
+        b.write_bytes(&[5; 6])?;
+        b.flush()?;
+
+        let mut bytes = [0u8; 2];
+        b.read_bytes(&mut bytes)?;
+        assert_eq!(bytes, [1, 2]);
+        b.read_bytes(&mut bytes)?;
+        assert_eq!(bytes, [3, 4]);
+        let mut bytes = [0u8; 6];
+        a.read_bytes(&mut bytes)?;
+        assert_eq!(bytes, [5; 6]);
+
+        // the peer is gone
+        drop(a);
+        let err = b.read_bytes(&mut bytes).unwrap_err();
+        assert!(matches!(OTError::from(err), OTError::PeerAbort));
+        b.write_bytes(&[0])?;
+        assert!(matches!(
+            OTError::from(b.flush().unwrap_err()),
+            OTError::PeerAbort
+        ));
+
+        Ok(())
+    }
+}
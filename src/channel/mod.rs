@@ -4,22 +4,55 @@ use std::{
     rc::Rc,
 };
 
+use ark_ec::CurveGroup;
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
 
 use crate::types::*;
 
+mod mem;
+mod mux;
+mod sync;
+mod tcp;
+mod track;
+
+pub use mem::MemChannel;
+pub use mux::{MuxChannel, MuxChannels};
+pub use sync::SyncChannel;
+pub use tcp::TcpChannel;
+pub use track::{CommStats, Counters, TrackChannel};
+
 pub trait AbstractChannel {
     fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ChannelError>;
 
     fn write_zp(&mut self, val: Zp) -> Result<(), ChannelError>;
 
-    fn write_g(&mut self, val: G) -> Result<(), ChannelError>;
+    fn write_g(&mut self, val: G) -> Result<(), ChannelError> {
+        self.write_group(val)
+    }
+
+    /// Write a point of any curve in compressed form
+    fn write_group<P: CurveGroup>(&mut self, val: P) -> Result<(), ChannelError> {
+        let mut buff = Vec::new();
+        val.serialize_compressed(&mut buff)?;
+        self.write_bytes(&buff)
+    }
 
     fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), ChannelError>;
 
     fn read_zp(&mut self) -> Result<Zp, ChannelError>;
 
-    fn read_g(&mut self) -> Result<G, ChannelError>;
+    fn read_g(&mut self) -> Result<G, ChannelError> {
+        self.read_group()
+    }
+
+    /// Read a point of any curve in compressed form.
+    /// The number of bytes read is the compressed size of the curve's points.
+    fn read_group<P: CurveGroup>(&mut self) -> Result<P, ChannelError> {
+        let mut buff = vec![0; P::generator().compressed_size()];
+        self.read_bytes(&mut buff)?;
+        let val = P::deserialize_compressed(&*buff)?;
+        Ok(val)
+    }
 
     fn flush(&mut self) -> Result<(), ChannelError>;
 
@@ -43,6 +76,10 @@ pub enum ChannelError {
         #[from]
         source: SerializationError,
     },
+    #[error("channel lock poisoned by a panicked thread")]
+    Poisoned,
+    #[error("invalid frame: {0}")]
+    InvalidFrame(String),
 }
 
 impl<R: Read, W: Write> Channel<R, W> {
@@ -76,13 +113,6 @@ impl<R: Read, W: Write> AbstractChannel for Channel<R, W> {
         self.write_bytes(&buff)
     }
 
-    #[inline(always)]
-    fn write_g(&mut self, val: G) -> Result<(), ChannelError> {
-        let mut buff = Vec::new();
-        val.serialize_compressed(&mut buff)?;
-        self.write_bytes(&buff)
-    }
-
     #[inline(always)]
     fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), ChannelError> {
         self.reader.borrow_mut().read_exact(bytes)?;
@@ -97,14 +127,6 @@ impl<R: Read, W: Write> AbstractChannel for Channel<R, W> {
         Ok(val)
     }
 
-    #[inline(always)]
-    fn read_g(&mut self) -> Result<G, ChannelError> {
-        let mut buff = vec![0; 32];
-        self.read_bytes(&mut buff)?;
-        let val = G::deserialize_compressed(&*buff)?;
-        Ok(val)
-    }
-
     #[inline(always)]
     fn flush(&mut self) -> Result<(), ChannelError> {
         self.writer.borrow_mut().flush()?;
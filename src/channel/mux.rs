@@ -0,0 +1,292 @@
+//! Multiplexer splitting one channel into many logical channels.
+//!
+//! Each flush of a sub-channel sends its buffered bytes as frames `stream id || length || payload`,
+//! with the id and the length as little-endian `u32`. The reading side demultiplexes the frames
+//! into per-stream queues, so a sub-channel may receive frames of other streams before its own.
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use super::{AbstractChannel, ChannelError};
+use crate::types::Zp;
+
+/// Maximum payload of a frame. Longer flushes are split, longer incoming frames are rejected.
+const MAX_FRAME: usize = 1 << 20;
+
+/// Maximum number of bytes kept for the streams which are not opened yet
+const MAX_UNOPENED: usize = 16 * MAX_FRAME;
+
+/// Maximum number of bytes kept for all the streams, in addition to the bytes of the current read
+const MAX_QUEUED: usize = 32 * MAX_FRAME;
+
+struct Demux<C> {
+    channel: C,
+    // bytes received but not read yet by each stream
+    queues: HashMap<u32, VecDeque<u8>>,
+    opened: HashSet<u32>,
+    // bytes queued for all the streams
+    queued: usize,
+    // bytes queued for the streams which are not opened
+    unopened: usize,
+}
+
+impl<C: AbstractChannel> Demux<C> {
+    /// Read one frame from the underlying channel into the queue of its stream.
+    /// `requested` is the length of the read waiting for the frame, which is not limited.
+    fn read_frame(&mut self, requested: usize) -> Result<(), ChannelError> {
+        let mut header = [0u8; 8];
+        self.channel.read_bytes(&mut header)?;
+        let id = u32::from_le_bytes(std::array::from_fn(|i| header[i]));
+        let len = u32::from_le_bytes(std::array::from_fn(|i| header[4 + i])) as usize;
+        if len > MAX_FRAME {
+            return Err(ChannelError::InvalidFrame(format!(
+                "frame of {len} bytes on stream {id} exceeds {MAX_FRAME} bytes"
+            )));
+        }
+        self.queued += len;
+        if self.queued > MAX_QUEUED + requested {
+            return Err(ChannelError::InvalidFrame(format!(
+                "more than {MAX_QUEUED} bytes received but not read"
+            )));
+        }
+        if !self.opened.contains(&id) {
+            self.unopened += len;
+            if self.unopened > MAX_UNOPENED {
+                return Err(ChannelError::InvalidFrame(format!(
+                    "more than {MAX_UNOPENED} bytes received for streams which are not opened"
+                )));
+            }
+        }
+        let mut payload = vec![0u8; len];
+        self.channel.read_bytes(&mut payload)?;
+        self.queues.entry(id).or_default().extend(payload);
+        Ok(())
+    }
+
+    fn open(&mut self, id: u32) {
+        if self.opened.insert(id) {
+            self.unopened -= self.queues.get(&id).map_or(0, |q| q.len());
+        }
+    }
+}
+
+struct Mux<C> {
+    channel: C,
+    // bytes written but not flushed yet by each stream
+    pending: HashMap<u32, Vec<u8>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, ChannelError> {
+    mutex.lock().map_err(|_| ChannelError::Poisoned)
+}
+
+/// Splits one channel into logical channels identified by a stream id.
+///
+/// Both parties open the sub-channels with the same ids, e.g. one for the base OTs and one for
+/// the extension. Frames of a stream which is not opened yet are kept until it is, up to a limit
+/// on the bytes kept for all such streams. The bytes received but not read yet are limited too,
+/// so the peer cannot flood a stream nobody is reading.
+///
+/// Reading and writing use different clones of the underlying channel, so with a `SyncChannel`
+/// a thread blocked reading does not block the writers. A sub-channel reading waits for the
+/// underlying channel while holding the demultiplexer, so threads of one party reading from
+/// different streams at the same time can deadlock if the peer answers them out of order.
+pub struct MuxChannels<C: AbstractChannel> {
+    demux: Arc<Mutex<Demux<C>>>,
+    mux: Arc<Mutex<Mux<C>>>,
+}
+
+impl<C: AbstractChannel> MuxChannels<C> {
+    pub fn new(channel: C) -> Self {
+        Self {
+            demux: Arc::new(Mutex::new(Demux {
+                channel: channel.clone(),
+                queues: HashMap::new(),
+                opened: HashSet::new(),
+                queued: 0,
+                unopened: 0,
+            })),
+            mux: Arc::new(Mutex::new(Mux {
+                channel,
+                pending: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Logical channel with the given stream id. Channels opened with the same id share their
+    /// stream, like clones.
+    pub fn channel(&self, id: u32) -> MuxChannel<C> {
+        // the queues are still consistent if a thread panicked while holding the lock
+        self.demux
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .open(id);
+        MuxChannel {
+            id,
+            demux: self.demux.clone(),
+            mux: self.mux.clone(),
+        }
+    }
+}
+
+/// Logical channel of `MuxChannels`
+pub struct MuxChannel<C: AbstractChannel> {
+    id: u32,
+    demux: Arc<Mutex<Demux<C>>>,
+    mux: Arc<Mutex<Mux<C>>>,
+}
+
+impl<C: AbstractChannel> MuxChannel<C> {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl<C: AbstractChannel> AbstractChannel for MuxChannel<C> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ChannelError> {
+        lock(&self.mux)?
+            .pending
+            .entry(self.id)
+            .or_default()
+            .extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn write_zp(&mut self, val: Zp) -> Result<(), ChannelError> {
+        let mut buff = Vec::new();
+        val.serialize_compressed(&mut buff)?;
+        self.write_bytes(&buff)
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), ChannelError> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        let len = bytes.len();
+        let mut demux = lock(&self.demux)?;
+        while demux.queues.get(&self.id).map_or(0, |q| q.len()) < len {
+            demux.read_frame(len)?;
+        }
+        let queue = demux.queues.entry(self.id).or_default();
+        for (b, q) in bytes.iter_mut().zip(queue.drain(..len)) {
+            *b = q;
+        }
+        demux.queued -= len;
+        Ok(())
+    }
+
+    fn read_zp(&mut self) -> Result<Zp, ChannelError> {
+        let mut buff = vec![0; 32];
+        self.read_bytes(&mut buff)?;
+        let val = Zp::deserialize_compressed(&*buff)?;
+        Ok(val)
+    }
+
+    fn flush(&mut self) -> Result<(), ChannelError> {
+        let mut mux = lock(&self.mux)?;
+        let payload = mux.pending.remove(&self.id).unwrap_or_default();
+        for chunk in payload.chunks(MAX_FRAME) {
+            mux.channel.write_bytes(&self.id.to_le_bytes())?;
+            mux.channel
+                .write_bytes(&(chunk.len() as u32).to_le_bytes())?;
+            mux.channel.write_bytes(chunk)?;
+        }
+        mux.channel.flush()
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            id: self.id,
+            demux: self.demux.clone(),
+            mux: self.mux.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{channel::MemChannel, ot::OTError};
+
+    #[test]
+    fn test_mux_channel() -> Result<(), ChannelError> {
+        let (a, b) = MemChannel::pair();
+        let (a, b) = (MuxChannels::new(a), MuxChannels::new(b));
+        let (mut a1, mut a2) = (a.channel(1), a.channel(2));
+
+        // stream 2 is flushed first, and stream 1 is read first
+        a1.write_bytes(&[1; 3])?;
+        a2.write_bytes(&[2; 5])?;
+        a2.flush()?;
+        a1.write_bytes(&[1; 2])?;
+        a1.flush()?;
+        a1.write_zp(Zp::from(7u64))?;
+        a1.flush()?;
+
+        // b2 is opened after its frame arrived
+        let mut b1 = b.channel(1);
+        let mut bytes = [0u8; 5];
+        b1.read_bytes(&mut bytes)?;
+        assert_eq!(bytes, [1; 5]);
+        assert_eq!(b1.read_zp()?, Zp::from(7u64));
+        let mut b2 = b.channel(2);
+        b2.read_bytes(&mut bytes)?;
+        assert_eq!(bytes, [2; 5]);
+
+        // a flush larger than a frame is split
+        let long = (0..MAX_FRAME + 10).map(|i| i as u8).collect::<Vec<_>>();
+        b2.write_bytes(&long)?;
+        b2.flush()?;
+        let mut received = vec![0u8; long.len()];
+        a2.read_bytes(&mut received)?;
+        assert_eq!(received, long);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_frame() -> Result<(), ChannelError> {
+        let (mut a, b) = MemChannel::pair();
+        a.write_bytes(&0u32.to_le_bytes())?;
+        a.write_bytes(&u32::MAX.to_le_bytes())?;
+        a.flush()?;
+
+        let mut b = MuxChannels::new(b).channel(0);
+        let err = b.read_bytes(&mut [0u8; 1]).unwrap_err();
+        assert!(matches!(OTError::from(err), OTError::MalformedMessage(_)));
+
+        // too much data for a stream which is never opened
+        let (a, b) = MemChannel::pair();
+        let mut a = MuxChannels::new(a).channel(7);
+        a.write_bytes(&vec![0u8; MAX_UNOPENED + 1])?;
+        a.flush()?;
+        let mut b = MuxChannels::new(b).channel(0);
+        b.read_bytes(&mut [])?;
+        let err = b.read_bytes(&mut [0u8; 1]).unwrap_err();
+        assert!(matches!(OTError::from(err), OTError::MalformedMessage(_)));
+
+        // an open stream is flooded while another one is read
+        let (a, b) = MemChannel::pair();
+        let mut a = MuxChannels::new(a).channel(1);
+        a.write_bytes(&vec![0u8; MAX_QUEUED + MAX_FRAME])?;
+        a.flush()?;
+        let b = MuxChannels::new(b);
+        let _b1 = b.channel(1);
+        let err = b.channel(0).read_bytes(&mut [0u8; 1]).unwrap_err();
+        assert!(matches!(OTError::from(err), OTError::MalformedMessage(_)));
+
+        // the peer is gone in the middle of a frame
+        let (mut a, b) = MemChannel::pair();
+        a.write_bytes(&[0, 0, 0, 0, 8, 0, 0, 0, 1])?;
+        a.flush()?;
+        drop(a);
+        let mut b = MuxChannels::new(b).channel(0);
+        let err = b.read_bytes(&mut [0u8; 1]).unwrap_err();
+        assert!(matches!(OTError::from(err), OTError::PeerAbort));
+
+        Ok(())
+    }
+}
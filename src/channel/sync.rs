@@ -0,0 +1,156 @@
+//! Channel which can be moved to and shared with other threads.
+use std::{
+    io::{Read, Write},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use super::{AbstractChannel, ChannelError};
+use crate::types::Zp;
+
+/// Channel whose halves are shared with `Arc<Mutex<_>>`, so that it is `Send` and `Sync`
+/// whenever the reader and the writer are `Send`.
+///
+/// Each method locks the half it uses for the duration of the call, so a message written by
+/// one `write_bytes` is never interleaved with another thread's, but a message split across
+/// calls can be. Threads sharing a channel have to agree on the order of their messages.
+pub struct SyncChannel<R, W> {
+    reader: Arc<Mutex<R>>,
+    writer: Arc<Mutex<W>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>, ChannelError> {
+    mutex.lock().map_err(|_| ChannelError::Poisoned)
+}
+
+impl<R: Read, W: Write> SyncChannel<R, W> {
+    pub fn new(reader: R, writer: W) -> Self {
+        Self {
+            reader: Arc::new(Mutex::new(reader)),
+            writer: Arc::new(Mutex::new(writer)),
+        }
+    }
+
+    pub fn reader(self) -> Arc<Mutex<R>> {
+        self.reader
+    }
+
+    pub fn writer(self) -> Arc<Mutex<W>> {
+        self.writer
+    }
+}
+
+impl<R: Read, W: Write> AbstractChannel for SyncChannel<R, W> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ChannelError> {
+        lock(&self.writer)?.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn write_zp(&mut self, val: Zp) -> Result<(), ChannelError> {
+        let mut buff = Vec::new();
+        val.serialize_compressed(&mut buff)?;
+        self.write_bytes(&buff)
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), ChannelError> {
+        lock(&self.reader)?.read_exact(bytes)?;
+        Ok(())
+    }
+
+    fn read_zp(&mut self) -> Result<Zp, ChannelError> {
+        let mut buff = vec![0; 32];
+        self.read_bytes(&mut buff)?;
+        let val = Zp::deserialize_compressed(&*buff)?;
+        Ok(val)
+    }
+
+    fn flush(&mut self) -> Result<(), ChannelError> {
+        lock(&self.writer)?.flush()?;
+        Ok(())
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            reader: self.reader.clone(),
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{
+        block::Block128,
+        channel::MemChannel,
+        ot::{
+            co15::{CO15Receiver, CO15Sender},
+            OTReceiver, OTSender,
+        },
+    };
+
+    #[test]
+    fn test_send_channel() -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        // the receiver is set up here and moved to another thread with its channel
+        let sender_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut sender = CO15Sender::setup(sender_channel, &mut rng)?;
+            sender.send_batch(&[[Block128::from(1), Block128::from(2)]; 3])
+        });
+        let mut receiver = CO15Receiver::setup(receiver_channel)?;
+
+        let handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            receiver.receive_batch::<2, Block128, ThreadRng>(&[1, 0, 1], &mut rng)
+        });
+        sender_handle.join().unwrap()?;
+        let received = handle.join().unwrap()?;
+        assert_eq!(
+            received,
+            vec![Block128::from(2), Block128::from(1), Block128::from(2)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_channel() -> Result<(), Box<dyn std::error::Error>> {
+        // clones share the stream from different threads
+        let (mut a, mut b) = MemChannel::pair();
+        let workers = (0..4u8)
+            .map(|i| {
+                let mut a = a.clone();
+                thread::spawn(move || {
+                    a.write_bytes(&[i; 8])?;
+                    a.flush()
+                })
+            })
+            .collect::<Vec<_>>();
+        for w in workers {
+            w.join().unwrap()?;
+        }
+        let mut seen = (0..4)
+            .map(|_| {
+                let mut bytes = [0u8; 8];
+                b.read_bytes(&mut bytes)?;
+                // messages of one call are not interleaved
+                assert!(bytes.iter().all(|&x| x == bytes[0]));
+                Ok(bytes[0])
+            })
+            .collect::<Result<Vec<_>, ChannelError>>()?;
+        seen.sort();
+        assert_eq!(seen, vec![0, 1, 2, 3]);
+        a.write_zp(Zp::from(5u64))?;
+        a.flush()?;
+        assert_eq!(b.read_zp()?, Zp::from(5u64));
+
+        Ok(())
+    }
+}
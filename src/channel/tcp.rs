@@ -0,0 +1,134 @@
+//! Channel over a TCP connection between two hosts.
+use std::{
+    io::{BufReader, BufWriter},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+    time::Duration,
+};
+
+use super::{Channel, ChannelError};
+
+/// Number of connection attempts of `TcpChannel::connect`
+const CONNECT_RETRIES: usize = 50;
+
+/// Interval between connection attempts of `TcpChannel::connect`
+const CONNECT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Buffered channel over a TCP stream
+pub type TcpChannel = Channel<BufReader<TcpStream>, BufWriter<TcpStream>>;
+
+impl TcpChannel {
+    /// Wrap a connected stream with buffered halves.
+    /// Nagle's algorithm is disabled, since the protocols flush after each message.
+    pub fn from_stream(stream: TcpStream) -> Result<Self, ChannelError> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let writer = BufWriter::new(stream);
+        Ok(Channel::new(reader, writer))
+    }
+
+    /// Listen on addr and wait for one peer to connect
+    pub fn listen<A: ToSocketAddrs>(addr: A) -> Result<Self, ChannelError> {
+        let listener = TcpListener::bind(addr)?;
+        Self::accept(&listener)
+    }
+
+    /// Wait for one peer to connect to the listener
+    pub fn accept(listener: &TcpListener) -> Result<Self, ChannelError> {
+        let (stream, _) = listener.accept()?;
+        Self::from_stream(stream)
+    }
+
+    /// Connect to the peer at addr.
+    /// The peer may not be listening yet, so the connection is retried `CONNECT_RETRIES` times.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Self, ChannelError> {
+        Self::connect_with_retry(addr, CONNECT_RETRIES, CONNECT_INTERVAL)
+    }
+
+    /// Connect to the peer at addr, trying at most `retries` times with `interval` in between
+    pub fn connect_with_retry<A: ToSocketAddrs>(
+        addr: A,
+        retries: usize,
+        interval: Duration,
+    ) -> Result<Self, ChannelError> {
+        let mut attempt = 1;
+        loop {
+            match TcpStream::connect(&addr) {
+                Ok(stream) => return Self::from_stream(stream),
+                Err(e) if attempt >= retries => return Err(e.into()),
+                Err(_) => {
+                    attempt += 1;
+                    thread::sleep(interval);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::thread_rng;
+
+    use super::*;
+    use crate::{
+        channel::AbstractChannel,
+        ot::co15::{CO15Receiver, CO15Sender},
+        vole::ot_vole::{VoleReceiver, VoleSender},
+    };
+
+    #[test]
+    fn test_tcp_vole() -> Result<(), Box<dyn std::error::Error>> {
+        const N: usize = 2;
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+
+        let sender_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let channel = TcpChannel::connect(addr)?;
+            let ot_sender = CO15Sender::setup(channel.clone(), &mut rng)?;
+            let mut sender = VoleSender::new(ot_sender, channel, &mut rng);
+            let keys = sender.send(N, &mut rng)?;
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>((sender.delta(), keys))
+        });
+
+        let mut rng = thread_rng();
+        let channel = TcpChannel::accept(&listener)?;
+        let ot_receiver = CO15Receiver::setup(channel.clone())?;
+        let mut receiver = VoleReceiver::new(ot_receiver, channel);
+        let received = receiver.receive(N, &mut rng)?;
+
+        let (delta, keys) = sender_handle.join().unwrap().unwrap();
+        for (k, (x, y)) in keys.iter().zip(received) {
+            assert_eq!(y, *k + x * delta);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_connect_retry() -> Result<(), Box<dyn std::error::Error>> {
+        // the listener stays bound, so the first attempt succeeds
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+
+        let handle = thread::spawn(move || {
+            let mut channel = TcpChannel::accept(&listener)?;
+            channel.write_bytes(b"ready")?;
+            channel.flush()?;
+            Ok::<_, ChannelError>(())
+        });
+
+        let mut channel = TcpChannel::connect_with_retry(addr, 2, Duration::from_millis(1))?;
+        let mut bytes = [0u8; 5];
+        channel.read_bytes(&mut bytes)?;
+        assert_eq!(&bytes, b"ready");
+        handle.join().unwrap()?;
+
+        // nothing listens on the privileged port 1, which is outside the ephemeral range
+        assert!(
+            TcpChannel::connect_with_retry("127.0.0.1:1", 2, Duration::from_millis(1)).is_err()
+        );
+
+        Ok(())
+    }
+}
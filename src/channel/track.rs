@@ -0,0 +1,285 @@
+//! Channel decorator which accounts the communication of a protocol.
+use std::{
+    fmt,
+    ops::AddAssign,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+use super::{AbstractChannel, ChannelError};
+use crate::types::Zp;
+
+/// Communication counters of one party
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Counters {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// Number of `write_*` calls
+    pub messages_sent: u64,
+    /// Number of `read_*` calls
+    pub messages_received: u64,
+    /// Number of flushes, which is the number of rounds of a protocol flushing once per round
+    pub flushes: u64,
+}
+
+impl AddAssign for Counters {
+    fn add_assign(&mut self, rhs: Self) {
+        self.bytes_sent += rhs.bytes_sent;
+        self.bytes_received += rhs.bytes_received;
+        self.messages_sent += rhs.messages_sent;
+        self.messages_received += rhs.messages_received;
+        self.flushes += rhs.flushes;
+    }
+}
+
+impl fmt::Display for Counters {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sent {} B in {} messages, received {} B in {} messages, {} flushes",
+            self.bytes_sent,
+            self.messages_sent,
+            self.bytes_received,
+            self.messages_received,
+            self.flushes
+        )
+    }
+}
+
+/// Snapshot of the statistics of a `TrackChannel`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CommStats {
+    /// Counters of all the communication
+    pub total: Counters,
+    /// Counters of each named phase, in the order the phases were first entered
+    pub phases: Vec<(String, Counters)>,
+}
+
+impl CommStats {
+    /// Counters of the phase, if it was entered
+    pub fn phase(&self, name: &str) -> Option<&Counters> {
+        self.phases.iter().find(|(n, _)| n == name).map(|(_, c)| c)
+    }
+
+    /// Add the statistics of another channel, e.g. when a protocol uses several connections.
+    /// Phases with the same name are summed up.
+    pub fn merge(&mut self, other: &CommStats) {
+        self.total += other.total;
+        for (name, counters) in other.phases.iter() {
+            match self.phases.iter_mut().find(|(n, _)| n == name) {
+                Some((_, c)) => *c += *counters,
+                None => self.phases.push((name.clone(), *counters)),
+            }
+        }
+    }
+}
+
+impl fmt::Display for CommStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "total: {}", self.total)?;
+        for (name, counters) in self.phases.iter() {
+            write!(f, "\n{name}: {counters}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct State {
+    stats: CommStats,
+    // index of the current phase in stats.phases
+    phase: Option<usize>,
+}
+
+impl State {
+    fn record(&mut self, f: impl Fn(&mut Counters)) {
+        f(&mut self.stats.total);
+        if let Some(i) = self.phase {
+            f(&mut self.stats.phases[i].1);
+        }
+    }
+}
+
+/// Channel recording the communication through the inner channel.
+/// Clones share the statistics, so a protocol using several clones is accounted as a whole.
+pub struct TrackChannel<C: AbstractChannel> {
+    inner: C,
+    state: Arc<Mutex<State>>,
+}
+
+impl<C: AbstractChannel> TrackChannel<C> {
+    pub fn new(inner: C) -> Self {
+        Self {
+            inner,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    // the statistics are still valid if a thread panicked while holding the lock
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Account the following communication to the named phase as well as to the total.
+    /// Entering a phase again continues its counters.
+    pub fn set_phase(&self, name: &str) {
+        let mut state = self.state();
+        let index = match state.stats.phases.iter().position(|(n, _)| n == name) {
+            Some(i) => i,
+            None => {
+                state
+                    .stats
+                    .phases
+                    .push((name.to_string(), Counters::default()));
+                state.stats.phases.len() - 1
+            }
+        };
+        state.phase = Some(index);
+    }
+
+    /// Account the following communication to the total only
+    pub fn clear_phase(&self) {
+        self.state().phase = None;
+    }
+
+    /// Copy of the current statistics
+    pub fn snapshot(&self) -> CommStats {
+        self.state().stats.clone()
+    }
+
+    /// Reset all the counters and forget the phases
+    pub fn reset(&self) {
+        *self.state() = State::default();
+    }
+
+    pub fn into_inner(self) -> C {
+        self.inner
+    }
+}
+
+impl<C: AbstractChannel> AbstractChannel for TrackChannel<C> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), ChannelError> {
+        self.inner.write_bytes(bytes)?;
+        self.state().record(|c| {
+            c.bytes_sent += bytes.len() as u64;
+            c.messages_sent += 1;
+        });
+        Ok(())
+    }
+
+    fn write_zp(&mut self, val: Zp) -> Result<(), ChannelError> {
+        let mut buff = Vec::new();
+        val.serialize_compressed(&mut buff)?;
+        self.write_bytes(&buff)
+    }
+
+    fn read_bytes(&mut self, bytes: &mut [u8]) -> Result<(), ChannelError> {
+        self.inner.read_bytes(bytes)?;
+        self.state().record(|c| {
+            c.bytes_received += bytes.len() as u64;
+            c.messages_received += 1;
+        });
+        Ok(())
+    }
+
+    fn read_zp(&mut self) -> Result<Zp, ChannelError> {
+        let mut buff = vec![0; 32];
+        self.read_bytes(&mut buff)?;
+        let val = Zp::deserialize_compressed(&*buff)?;
+        Ok(val)
+    }
+
+    fn flush(&mut self) -> Result<(), ChannelError> {
+        self.inner.flush()?;
+        self.state().record(|c| c.flushes += 1);
+        Ok(())
+    }
+
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{
+        block::Block128,
+        channel::MemChannel,
+        ot::{
+            co15::{CO15Receiver, CO15Sender},
+            OTError, OTReceiver, OTSender,
+        },
+    };
+
+    #[test]
+    fn test_track_channel() -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let sender_channel = TrackChannel::new(sender_channel);
+        let receiver_channel = TrackChannel::new(receiver_channel);
+
+        let tracker = receiver_channel;
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            tracker.set_phase("base-ot");
+            let mut ot_receiver = CO15Receiver::setup(tracker.clone())?;
+            tracker.set_phase("transfer");
+            ot_receiver.receive_batch::<2, Block128, ThreadRng>(&[0, 1, 1], &mut rng)?;
+            Ok::<_, OTError>(tracker.snapshot())
+        });
+
+        let mut rng = thread_rng();
+        sender_channel.set_phase("base-ot");
+        let mut ot_sender = CO15Sender::setup(sender_channel.clone(), &mut rng)?;
+        sender_channel.set_phase("transfer");
+        ot_sender.send_batch(&[[Block128::from(0), Block128::from(1)]; 3])?;
+        let sender_stats = sender_channel.snapshot();
+        let receiver_stats = receiver_handle.join().unwrap()?;
+
+        // S, then three R and 2 * 3 ciphertexts
+        let base_ot = sender_stats.phase("base-ot").unwrap();
+        assert_eq!(base_ot.bytes_sent, 32);
+        assert_eq!(base_ot.flushes, 1);
+        let transfer = sender_stats.phase("transfer").unwrap();
+        assert_eq!(transfer.bytes_received, 3 * 32);
+        assert_eq!(transfer.bytes_sent, 6 * 16);
+        assert_eq!(transfer.messages_sent, 6);
+        assert_eq!(sender_stats.total.bytes_sent, 32 + 6 * 16);
+
+        // each direction is seen by the other party
+        assert_eq!(
+            sender_stats.total.bytes_sent,
+            receiver_stats.total.bytes_received
+        );
+        assert_eq!(
+            sender_stats.total.bytes_received,
+            receiver_stats.total.bytes_sent
+        );
+        assert_eq!(receiver_stats.phases.len(), 2);
+
+        let mut merged = sender_stats.clone();
+        merged.merge(&receiver_stats);
+        assert_eq!(
+            merged.total.bytes_sent,
+            sender_stats.total.bytes_sent + receiver_stats.total.bytes_sent
+        );
+        assert_eq!(merged.phases.len(), 2);
+        assert_eq!(
+            merged.phase("transfer").unwrap().bytes_received,
+            transfer.bytes_received + receiver_stats.phase("transfer").unwrap().bytes_received
+        );
+
+        sender_channel.reset();
+        assert_eq!(sender_channel.snapshot(), CommStats::default());
+
+        Ok(())
+    }
+}
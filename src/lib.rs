@@ -1,9 +1,16 @@
 //! Implement vole based zkp
 pub mod block;
 pub mod ot;
+pub mod prg;
+pub mod ristretto;
+pub mod store;
+pub mod vole;
 
 mod channel;
 mod types;
-mod vole;
 
-pub use channel::{AbstractChannel, Channel};
+pub use channel::{
+    AbstractChannel, Channel, CommStats, Counters, MemChannel, MuxChannel, MuxChannels,
+    SyncChannel, TcpChannel, TrackChannel,
+};
+pub use types::{IsZero, Zp, G};
@@ -0,0 +1,219 @@
+//! Authenticated encryption of OT messages.
+//!
+//! A message m is encrypted under an OT key k as c = m ^ H(k, "pad", i) for each 32 byte
+//! chunk i, followed by the tag H(k, "mac", |m|, c) truncated to `TAG_LEN` bytes.
+//! Since every OT key is used for a single message, the hash based one-time pad is
+//! enough for confidentiality, and the tag lets the receiver detect a wrong key or a
+//! tampered ciphertext.
+use sha3::{Digest, Keccak256};
+
+use crate::{block::Block, channel::AbstractChannel};
+
+use super::{OTError, OTResult};
+
+/// Length of the MAC tag in bytes
+pub const TAG_LEN: usize = 16;
+
+/// Upper bound of a variable length message, checked by both the sender and the receiver
+pub const MAX_MESSAGE_LEN: usize = 1 << 24;
+
+/// Bytes read at once by `read_sealed`, so that the buffer grows with the data actually received
+/// rather than with the length claimed by the peer
+const READ_CHUNK: usize = 1 << 16;
+
+/// Encryption applied to the OT messages.
+/// Both parties have to use the same mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encryption {
+    /// Raw AES-256 block encryption with `Block::encrypt`
+    #[default]
+    Block,
+    /// Hash based one-time pad with MAC tag
+    Authenticated,
+}
+
+impl Encryption {
+    /// Length of the ciphertext of a block message
+    pub fn ciphertext_len<T: Block + Default>(&self) -> usize {
+        let len = T::default().bytes_len();
+        match self {
+            Encryption::Block => len,
+            Encryption::Authenticated => len + TAG_LEN,
+        }
+    }
+
+    /// Encrypt a block message
+    pub fn encrypt<T: Block>(&self, value: &T, key: &[u8; 32]) -> Vec<u8> {
+        match self {
+            Encryption::Block => value.encrypt(key).as_bytes(),
+            Encryption::Authenticated => seal(key, &value.as_bytes()),
+        }
+    }
+
+    /// Decrypt a block message encrypted by `encrypt`
+    pub fn decrypt<T: Block>(&self, ciphertext: &[u8], key: &[u8; 32]) -> OTResult<T> {
+        match self {
+            Encryption::Block => Ok(T::from_bytes(ciphertext).decrypt(key)),
+            Encryption::Authenticated => Ok(T::from_bytes(&open(key, ciphertext)?)),
+        }
+    }
+}
+
+fn hasher(key: &[u8; 32], domain: &[u8]) -> Keccak256 {
+    let mut hasher = Keccak256::default();
+    hasher.update(key);
+    hasher.update(domain);
+    hasher
+}
+
+/// XOR the key stream H(k, "pad", i) into the message
+fn apply_pad(key: &[u8; 32], msg: &mut [u8]) {
+    for (i, chunk) in msg.chunks_mut(32).enumerate() {
+        let mut hasher = hasher(key, b"pad");
+        hasher.update((i as u64).to_le_bytes());
+        let pad = hasher.finalize();
+        chunk.iter_mut().zip(pad).for_each(|(c, p)| *c ^= p);
+    }
+}
+
+fn tag(key: &[u8; 32], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+    let mut hasher = hasher(key, b"mac");
+    hasher.update((ciphertext.len() as u64).to_le_bytes());
+    hasher.update(ciphertext);
+    let digest = hasher.finalize();
+    std::array::from_fn(|i| digest[i])
+}
+
+/// Encrypt and authenticate the message. The output is `msg.len() + TAG_LEN` bytes.
+pub fn seal(key: &[u8; 32], msg: &[u8]) -> Vec<u8> {
+    let mut out = msg.to_vec();
+    apply_pad(key, &mut out);
+    let tag = tag(key, &out);
+    out.extend_from_slice(&tag);
+    out
+}
+
+/// Verify the tag and decrypt the message sealed by `seal`
+pub fn open(key: &[u8; 32], sealed: &[u8]) -> OTResult<Vec<u8>> {
+    if sealed.len() < TAG_LEN {
+        return Err(OTError::DecryptionFailed);
+    }
+    let (ciphertext, received) = sealed.split_at(sealed.len() - TAG_LEN);
+
+    // compare in constant time
+    let diff = tag(key, ciphertext)
+        .iter()
+        .zip(received)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if diff != 0 {
+        return Err(OTError::DecryptionFailed);
+    }
+
+    let mut msg = ciphertext.to_vec();
+    apply_pad(key, &mut msg);
+    Ok(msg)
+}
+
+/// Write a variable length message sealed under the key, prefixed by its length
+pub(crate) fn write_sealed<C: AbstractChannel>(
+    channel: &mut C,
+    key: &[u8; 32],
+    msg: &[u8],
+) -> OTResult<()> {
+    if msg.len() > MAX_MESSAGE_LEN {
+        return Err(OTError::InvalidParameter(format!(
+            "message length {} exceeds {MAX_MESSAGE_LEN}",
+            msg.len()
+        )));
+    }
+    let sealed = seal(key, msg);
+    channel.write_bytes(&(msg.len() as u32).to_le_bytes())?;
+    channel.write_bytes(&sealed)?;
+    Ok(())
+}
+
+/// Read a length prefixed sealed message written by `write_sealed`
+pub(crate) fn read_sealed<C: AbstractChannel>(channel: &mut C) -> OTResult<Vec<u8>> {
+    let mut len = [0u8; 4];
+    channel.read_bytes(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(OTError::MalformedMessage(format!(
+            "message length {len} exceeds {MAX_MESSAGE_LEN}"
+        )));
+    }
+
+    let total = len + TAG_LEN;
+    let mut sealed = Vec::with_capacity(total.min(READ_CHUNK));
+    while sealed.len() < total {
+        let start = sealed.len();
+        sealed.resize(total.min(start + READ_CHUNK), 0);
+        channel.read_bytes(&mut sealed[start..])?;
+    }
+    Ok(sealed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block::Block128, channel::MemChannel};
+
+    #[test]
+    fn test_seal_open() {
+        let key = [7u8; 32];
+        for len in [0, 1, 32, 33, 100] {
+            let msg = (0..len as u8).collect::<Vec<_>>();
+            let mut sealed = seal(&key, &msg);
+            assert_eq!(sealed.len(), len + TAG_LEN);
+            assert_eq!(open(&key, &sealed).unwrap(), msg);
+            assert!(matches!(
+                open(&[8u8; 32], &sealed),
+                Err(OTError::DecryptionFailed)
+            ));
+
+            sealed[0] ^= 1;
+            assert!(matches!(
+                open(&key, &sealed),
+                Err(OTError::DecryptionFailed)
+            ));
+        }
+
+        let value = Block128::from(42);
+        let ciphertext = Encryption::Authenticated.encrypt(&value, &key);
+        assert_eq!(
+            ciphertext.len(),
+            Encryption::Authenticated.ciphertext_len::<Block128>()
+        );
+        let decrypted: Block128 = Encryption::Authenticated
+            .decrypt(&ciphertext, &key)
+            .unwrap();
+        assert_eq!(decrypted, value);
+    }
+
+    #[test]
+    fn test_sealed_length() -> OTResult<()> {
+        let (mut a, mut b) = MemChannel::pair();
+        let key = [3u8; 32];
+
+        // longer than a read chunk
+        let msg = (0..3 * READ_CHUNK + 5).map(|i| i as u8).collect::<Vec<_>>();
+        write_sealed(&mut a, &key, &msg)?;
+        a.flush()?;
+        assert_eq!(open(&key, &read_sealed(&mut b)?)?, msg);
+
+        // the sender refuses a message the receiver would reject, without writing it
+        let long = vec![0u8; MAX_MESSAGE_LEN + 1];
+        assert!(matches!(
+            write_sealed(&mut a, &key, &long),
+            Err(OTError::InvalidParameter(_))
+        ));
+        a.write_bytes(&(MAX_MESSAGE_LEN as u32 + 1).to_le_bytes())?;
+        a.flush()?;
+        assert!(matches!(
+            read_sealed(&mut b),
+            Err(OTError::MalformedMessage(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,247 @@
+//! Beaver derandomization of random OT, described in "Precomputing Oblivious Transfer".
+//!
+//! Offline, the parties run random OTs where the sender gets keys (k_0, k_1) and the receiver
+//! gets (c, k_c) for a random c. Online, the receiver with choice b sends e = b ^ c and the
+//! sender replies Enc(k_e, m_0) and Enc(k_{1^e}, m_1), so that the receiver decrypts m_b with k_c.
+use std::collections::VecDeque;
+
+use ark_std::rand::Rng;
+
+use crate::{
+    block::{Block, Block256},
+    channel::AbstractChannel,
+};
+
+use super::{
+    check_arity, rot_key, OTError, OTReceiver, OTResult, OTSender, ROTReceiver, ROTSender,
+};
+
+fn not_enough(needed: usize, available: usize) -> OTError {
+    OTError::InvalidParameter(format!(
+        "{needed} OTs requested, but only {available} are precomputed"
+    ))
+}
+
+/// OT sender consuming random OTs precomputed with a random OT sender
+pub struct BeaverSender<S: ROTSender, C: AbstractChannel> {
+    rot: S,
+    channel: C,
+    // random OT keys (k_0, k_1) not used yet
+    pool: VecDeque<[[u8; 32]; 2]>,
+}
+
+impl<S: ROTSender, C: AbstractChannel> BeaverSender<S, C> {
+    pub fn new(rot: S, channel: C) -> Self {
+        Self {
+            rot,
+            channel,
+            pool: VecDeque::new(),
+        }
+    }
+
+    /// Run n random OTs and store them for the online phase
+    pub fn precompute(&mut self, n: usize) -> OTResult<()> {
+        for _ in 0..n {
+            let keys = self.rot.send_random::<2, Block256>()?;
+            self.pool.push_back(keys.map(rot_key));
+        }
+        Ok(())
+    }
+
+    /// Number of precomputed OTs not used yet
+    pub fn available(&self) -> usize {
+        self.pool.len()
+    }
+}
+
+impl<S: ROTSender, C: AbstractChannel> OTSender for BeaverSender<S, C> {
+    fn send<const N: usize, T: Block + Clone>(&mut self, values: [T; N]) -> OTResult<()> {
+        self.send_batch(&[values])
+    }
+
+    /// Receive the choice corrections of all the OTs in one message,
+    /// and reply all the masked messages at once.
+    fn send_batch<const N: usize, T: Block + Clone>(&mut self, values: &[[T; N]]) -> OTResult<()> {
+        check_arity::<N>()?;
+        if values.len() > self.pool.len() {
+            return Err(not_enough(values.len(), self.pool.len()));
+        }
+
+        let mut es = vec![0u8; values.len()];
+        self.channel.read_bytes(&mut es)?;
+        if let Some(e) = es.iter().find(|&&e| e > 1) {
+            return Err(OTError::MalformedMessage(format!("choice correction {e}")));
+        }
+
+        for (vs, e) in values.iter().zip(es) {
+            let keys = self.pool.pop_front().ok_or_else(|| not_enough(1, 0))?;
+            let e = e as usize;
+            for (i, v) in vs.iter().enumerate() {
+                self.channel
+                    .write_bytes(&v.encrypt(&keys[i ^ e]).as_bytes())?;
+            }
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+/// OT receiver consuming random OTs precomputed with a random OT receiver
+pub struct BeaverReceiver<Rot: ROTReceiver, C: AbstractChannel> {
+    rot: Rot,
+    channel: C,
+    // random choice c and key k_c not used yet
+    pool: VecDeque<(usize, [u8; 32])>,
+}
+
+impl<Rot: ROTReceiver, C: AbstractChannel> BeaverReceiver<Rot, C> {
+    pub fn new(rot: Rot, channel: C) -> Self {
+        Self {
+            rot,
+            channel,
+            pool: VecDeque::new(),
+        }
+    }
+
+    /// Run n random OTs with random choices and store them for the online phase
+    pub fn precompute<R: Rng>(&mut self, n: usize, rng: &mut R) -> OTResult<()> {
+        for _ in 0..n {
+            let c = rng.gen_range(0..2);
+            let k = self.rot.receive_random::<2, Block256, R>(c, rng)?;
+            self.pool.push_back((c, rot_key(k)));
+        }
+        Ok(())
+    }
+
+    /// Number of precomputed OTs not used yet
+    pub fn available(&self) -> usize {
+        self.pool.len()
+    }
+}
+
+impl<Rot: ROTReceiver, C: AbstractChannel> OTReceiver for BeaverReceiver<Rot, C> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let mut res = self.receive_batch::<N, T, R>(&[choice], rng)?;
+        Ok(res.remove(0))
+    }
+
+    /// Send the choice corrections of all the OTs in one message,
+    /// and receive all the masked messages at once.
+    fn receive_batch<const N: usize, T, R>(
+        &mut self,
+        choices: &[usize],
+        _rng: &mut R,
+    ) -> OTResult<Vec<T>>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        check_arity::<N>()?;
+        if let Some(&choice) = choices.iter().find(|&&c| c >= N) {
+            return Err(OTError::InvalidChoice(choice));
+        }
+        if choices.len() > self.pool.len() {
+            return Err(not_enough(choices.len(), self.pool.len()));
+        }
+
+        // e = b ^ c
+        let precomputed = self.pool.drain(..choices.len()).collect::<Vec<_>>();
+        let es = choices
+            .iter()
+            .zip(precomputed.iter())
+            .map(|(&b, (c, _))| (b ^ c) as u8)
+            .collect::<Vec<_>>();
+        self.channel.write_bytes(&es)?;
+        self.channel.flush()?;
+
+        let mut bytes = [
+            vec![0u8; T::default().bytes_len()],
+            vec![0u8; T::default().bytes_len()],
+        ];
+        choices
+            .iter()
+            .zip(precomputed)
+            .map(|(&choice, (_, k))| {
+                self.channel.read_bytes(&mut bytes[0])?;
+                self.channel.read_bytes(&mut bytes[1])?;
+                Ok(T::from_bytes(&bytes[choice]).decrypt(&k))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{
+        block::Block128,
+        channel::MemChannel,
+        ot::co15::{CO15Receiver, CO15Sender},
+    };
+
+    #[test]
+    fn test_beaver_ot() -> Result<(), Box<dyn std::error::Error>> {
+        let choices = vec![1, 0, 0, 1, 1];
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let rot = CO15Receiver::setup(ot_receiver_channel)?;
+            let mut receiver = BeaverReceiver::new(rot, receiver_channel);
+            receiver.precompute(choices.len() + 1, &mut rng)?;
+
+            let batch =
+                receiver.receive_batch::<2, Block128, ThreadRng>(&choices[1..], &mut rng)?;
+            let single = receiver.receive::<2, Block128, ThreadRng>(choices[0], &mut rng)?;
+            assert_eq!(receiver.available(), 1);
+            Ok::<_, OTError>((choices, single, batch))
+        });
+
+        let mut rng = thread_rng();
+        let rot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+        let mut sender = BeaverSender::new(rot, sender_channel);
+        sender.precompute(6)?;
+
+        let values = (0..5u128)
+            .map(|i| [Block128::from(2 * i), Block128::from(2 * i + 1)])
+            .collect::<Vec<_>>();
+        sender.send_batch(&values[1..])?;
+        sender.send(values[0])?;
+        assert_eq!(sender.available(), 1);
+
+        let (choices, single, batch) = receiver_handle.join().unwrap()?;
+        assert_eq!(single, values[0][choices[0]]);
+        for ((v, c), r) in values[1..].iter().zip(&choices[1..]).zip(batch) {
+            assert_eq!(v[*c], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_enough_precomputed() -> Result<(), Box<dyn std::error::Error>> {
+        let (ot_sender_channel, _ot_receiver_channel) = MemChannel::pair();
+        let (sender_channel, _receiver_channel) = MemChannel::pair();
+
+        // no random OT is precomputed, so the sender fails before any communication
+        let mut rng = thread_rng();
+        let rot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+        let mut sender = BeaverSender::new(rot, sender_channel);
+        assert!(matches!(
+            sender.send([Block128::from(0), Block128::from(1)]),
+            Err(OTError::InvalidParameter(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -1,33 +1,52 @@
 //! This module implements oblivious trasnfer implementation described in
 //! https://eprint.iacr.org/2015/267.pdf by Tung Chou and Claudio Orlandi
-use ark_ec::twisted_edwards::TECurveConfig;
-use ark_ed25519::EdwardsConfig;
-use ark_serialize::CanonicalSerialize;
+//!
+//! The protocol runs over any prime order group given by `ark_ec::CurveGroup`.
+//! It defaults to ed25519 with Ristretto255 encoding of the points, and `setup_with_group`
+//! selects another curve such as BLS12-381 G1 or secp256k1.
+use std::marker::PhantomData;
+
+use ark_ec::CurveGroup;
 use ark_std::{rand::Rng, UniformRand};
 use sha3::{Digest, Keccak256};
 
 use crate::{block::*, channel::AbstractChannel, types::*};
 
-use super::{OTReceiver, OTResult, OTSender, ROTReceiver, ROTSender};
+use super::{
+    auth::{self, Encryption},
+    encoding::{GroupEncoding, Ristretto},
+    OTError, OTReceiver, OTResult, OTSender, ROTReceiver, ROTSender,
+};
 
-pub struct CO15Sender<C: AbstractChannel> {
+/// CO15 sender over the group P, whose points are sent in encoding E
+pub struct CO15Sender<C: AbstractChannel, P: CurveGroup = G, E: GroupEncoding<P> = Ristretto> {
     pub channel: C,
 
-    y: Zp,
-    s: G,
-    t: G,
+    y: P::ScalarField,
+    s: P,
+    t: P,
+    encryption: Encryption,
+    _encoding: PhantomData<E>,
 }
 
 impl<C: AbstractChannel> CO15Sender<C> {
-    pub fn setup<R: Rng>(mut channel: C, rng: &mut R) -> OTResult<Self> {
-        // Group G is subset of points over twisted Edwards curve.
-        // −x^2 + y^2 = 1 + d x^2 y^2
-        // constant d and generator B can be found in https://eprint.iacr.org/2011/368.pdf
-        // the implementation comes from ark-works/ed25519
-        //
+    /// Setup the sender over ed25519.
+    ///
+    /// Group G is subset of points over twisted Edwards curve.
+    /// −x^2 + y^2 = 1 + d x^2 y^2
+    /// constant d and generator B can be found in https://eprint.iacr.org/2011/368.pdf
+    /// the implementation comes from ark-works/ed25519
+    pub fn setup<R: Rng>(channel: C, rng: &mut R) -> OTResult<Self> {
+        Self::setup_with_group(channel, rng)
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> CO15Sender<C, P, E> {
+    /// Setup the sender over the group P
+    pub fn setup_with_group<R: Rng>(mut channel: C, rng: &mut R) -> OTResult<Self> {
         // Samples y from Z_p
-        let y = Zp::rand(rng);
-        let b = EdwardsConfig::GENERATOR;
+        let y = P::ScalarField::rand(rng);
+        let b = P::generator();
 
         // Compute S = yB, T = yS
         // where B is a generator of group of prime order
@@ -35,201 +54,290 @@ impl<C: AbstractChannel> CO15Sender<C> {
         let t = s * y;
 
         // Send s to receiver
-        channel.write_g(s)?;
+        E::write(&mut channel, &s)?;
         channel.flush()?;
 
-        Ok(Self { channel, y, s, t })
+        Ok(Self {
+            channel,
+            y,
+            s,
+            t,
+            encryption: Encryption::default(),
+            _encoding: PhantomData,
+        })
     }
-}
 
-impl<C: AbstractChannel> OTSender for CO15Sender<C> {
-    fn send<const N: usize, T: Block + Clone>(&mut self, values: [T; N]) -> OTResult<()> {
-        // Receive r from receiver
-        let r = self.channel.read_g()?;
-
-        let mut r_buff = Vec::new();
-        let mut s_buff = Vec::new();
+    /// Set the encryption of the messages. The receiver has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+}
 
-        r.serialize_compressed(&mut r_buff)?;
-        self.s.serialize_compressed(&mut s_buff)?;
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> CO15Sender<C, P, E> {
+    /// Hasher initialized with H(S, R)
+    fn hasher(&self, r: &P) -> OTResult<Keccak256> {
+        let r_buff = E::encode(r)?;
+        let s_buff = E::encode(&self.s)?;
 
         let mut hasher = Keccak256::default();
         hasher.update(s_buff);
         hasher.update(r_buff);
-        // println!("values len: {:?}", values.len());
+        Ok(hasher)
+    }
 
-        // Receive r from receiver
-        // calculate keys using r
-        // k_j = H (S,R )(yR − jT)
-        for (i, v) in values.iter().enumerate() {
+    /// Compute keys k_j = H(S, R)(yR − jT) for j in 0..N
+    fn keys<const N: usize>(&self, r: &P) -> OTResult<[[u8; 32]; N]> {
+        let hasher = self.hasher(r)?;
+        let mut keys = [[0u8; 32]; N];
+        for (i, key) in keys.iter_mut().enumerate() {
             let mut hasher = hasher.clone();
-            let k = r * self.y - self.t * Zp::from(i as u32);
-            let mut buff = Vec::new();
-            k.serialize_compressed(&mut buff)?;
-
-            hasher.update(buff);
-            let key = hasher.finalize();
-            let encrypted = v.encrypt(&key.into());
-
-            // println!("encrypetd len: {:?}", encrypted.as_bytes());
-            // send ciphertext to receiver
-            self.channel.write_bytes(&encrypted.as_bytes())?;
-            self.channel.flush()?;
+            let k = *r * self.y - self.t * P::ScalarField::from(i as u64);
+            hasher.update(E::encode(&k)?);
+            *key = hasher.finalize().into();
         }
+        Ok(keys)
+    }
+
+    /// Receive R of n OTs from receiver
+    fn read_rs(&mut self, n: usize) -> OTResult<Vec<P>> {
+        (0..n).map(|_| E::read(&mut self.channel)).collect()
+    }
+
+    /// Send variable length messages with authenticated encryption.
+    /// The receiver learns the length of all the messages but the content of the chosen one only.
+    pub fn send_bytes<const N: usize>(&mut self, messages: &[[Vec<u8>; N]]) -> OTResult<()> {
+        let rs = self.read_rs(messages.len())?;
+        for (r, ms) in rs.iter().zip(messages) {
+            let keys = self.keys::<N>(r)?;
+            for (m, key) in ms.iter().zip(keys) {
+                auth::write_sealed(&mut self.channel, &key, m)?;
+            }
+        }
+        self.channel.flush()?;
 
         Ok(())
     }
 }
 
-impl<C: AbstractChannel> ROTSender for CO15Sender<C> {
-    fn send_random<const N: usize, T: Block>(&mut self) -> OTResult<[T; N]> {
-        // Receive r from receiver
-        let r = self.channel.read_g()?;
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> OTSender for CO15Sender<C, P, E> {
+    fn send<const N: usize, T: Block + Clone>(&mut self, values: [T; N]) -> OTResult<()> {
+        self.send_batch(&[values])
+    }
 
-        let mut r_buff = Vec::new();
-        let mut s_buff = Vec::new();
+    /// Receive all R from receiver in one message, and reply all the ciphertexts at once.
+    fn send_batch<const N: usize, T: Block + Clone>(&mut self, values: &[[T; N]]) -> OTResult<()> {
+        // Receive r from receiver
+        let rs = self.read_rs(values.len())?;
 
-        r.serialize_compressed(&mut r_buff)?;
-        self.s.serialize_compressed(&mut s_buff)?;
+        // calculate keys using r
+        // k_j = H (S,R )(yR − jT)
+        for (r, vs) in rs.iter().zip(values) {
+            let keys = self.keys::<N>(r)?;
+            for (v, key) in vs.iter().zip(keys) {
+                // send ciphertext to receiver
+                let encrypted = self.encryption.encrypt(v, &key);
+                self.channel.write_bytes(&encrypted)?;
+            }
+        }
+        self.channel.flush()?;
 
-        let mut hasher = Keccak256::default();
-        hasher.update(s_buff);
-        hasher.update(r_buff);
+        Ok(())
+    }
+}
 
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> ROTSender for CO15Sender<C, P, E> {
+    fn send_random<const N: usize, T: Block>(&mut self) -> OTResult<[T; N]> {
         // Receive r from receiver
+        let r = E::read(&mut self.channel)?;
+
         // calculate keys using r
         // k_j = H (S,R )(yR − jT)
-        let keys: [T; N] = std::array::from_fn(|i| {
-            let mut hasher = hasher.clone();
-            let k = r * self.y - self.t * Zp::from(i as u32);
-            let mut buff = Vec::new();
-            k.serialize_compressed(&mut buff).unwrap();
-
-            hasher.update(buff);
-            let key = hasher.finalize();
-            T::from_bytes(&key)
-        });
-
-        Ok(keys)
+        let keys = self.keys::<N>(&r)?;
+        Ok(keys.map(|key| T::from_bytes(&key)))
     }
 }
 
-pub struct CO15Receiver<C: AbstractChannel> {
+/// CO15 receiver over the group P, whose points are sent in encoding E
+pub struct CO15Receiver<C: AbstractChannel, P: CurveGroup = G, E: GroupEncoding<P> = Ristretto> {
     pub channel: C,
-    s: G,
+    s: P,
+    encryption: Encryption,
+    _encoding: PhantomData<E>,
 }
 
 impl<C: AbstractChannel> CO15Receiver<C> {
+    /// Setup the receiver over ed25519
+    pub fn setup(channel: C) -> OTResult<Self> {
+        Self::setup_with_group(channel)
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> CO15Receiver<C, P, E> {
     /// receive s value from sender
-    pub fn setup(mut channel: C) -> OTResult<Self> {
-        let s = channel.read_g()?;
+    pub fn setup_with_group(mut channel: C) -> OTResult<Self> {
+        let s = E::read(&mut channel)?;
         channel.flush()?;
-        Ok(Self { channel, s })
+        Ok(Self {
+            channel,
+            s,
+            encryption: Encryption::default(),
+            _encoding: PhantomData,
+        })
     }
-}
 
-impl<C: AbstractChannel> OTReceiver for CO15Receiver<C> {
-    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
-    where
-        T: Block + Clone + Default,
-        R: Rng,
-    {
-        // sample x from Z_p for N times
-        // Compute R = cS + xB
-        // where c is a choice
-        let x = Zp::rand(rng);
-        let b = EdwardsConfig::GENERATOR;
-        let r = self.s * Zp::from(choice as u32) + b * x;
-        self.channel.write_g(r)?;
-        self.channel.flush()?;
+    /// Set the encryption of the messages. The sender has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+}
 
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> CO15Receiver<C, P, E> {
+    /// Compute R = cS + xB and the key H(S, R)(xS)
+    fn choose(&self, choice: usize, x: P::ScalarField) -> OTResult<(P, [u8; 32])> {
+        let b = P::generator();
+        let r = self.s * P::ScalarField::from(choice as u64) + b * x;
         let k = self.s * x;
 
         // calculate key
-        let mut r_buff = Vec::new();
-        let mut s_buff = Vec::new();
-        let mut k_buff = Vec::new();
-
-        r.serialize_compressed(&mut r_buff)?;
-        self.s.serialize_compressed(&mut s_buff)?;
-        k.serialize_compressed(&mut k_buff)?;
+        let r_buff = E::encode(&r)?;
+        let s_buff = E::encode(&self.s)?;
+        let k_buff = E::encode(&k)?;
 
         let mut hasher = Keccak256::default();
         hasher.update(s_buff);
         hasher.update(r_buff);
         hasher.update(k_buff);
 
-        let key = hasher.finalize();
+        Ok((r, hasher.finalize().into()))
+    }
 
-        let d = T::default();
-        let ciphertexts: [T; N] = std::array::from_fn(|_| {
-            let mut bytes = vec![0u8; d.bytes_len()];
-            // TODO: handle result correctly
-            self.channel.read_bytes(&mut bytes).unwrap();
-            T::from_bytes(&bytes)
-        });
+    /// Send R for each choice in one message and return the keys of the choices
+    fn send_choices<const N: usize, R: Rng>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<[u8; 32]>> {
+        if let Some(&choice) = choices.iter().find(|&&c| c >= N) {
+            return Err(OTError::InvalidChoice(choice));
+        }
+
+        // sample x from Z_p for each OT
+        // Compute R = cS + xB
+        // where c is a choice
+        let keys = choices
+            .iter()
+            .map(|&choice| {
+                let (r, key) = self.choose(choice, P::ScalarField::rand(rng))?;
+                E::write(&mut self.channel, &r)?;
+                Ok(key)
+            })
+            .collect::<OTResult<Vec<_>>>()?;
+        self.channel.flush()?;
+
+        Ok(keys)
+    }
+
+    /// Receive variable length messages sent by `CO15Sender::send_bytes`.
+    /// Returns `OTError::DecryptionFailed` if the chosen message fails authentication.
+    pub fn receive_bytes<const N: usize, R: Rng>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<Vec<u8>>> {
+        let keys = self.send_choices::<N, R>(choices, rng)?;
+
+        choices
+            .iter()
+            .zip(keys)
+            .map(|(&choice, key)| {
+                let mut res = None;
+                for i in 0..N {
+                    let sealed = auth::read_sealed(&mut self.channel)?;
+                    if i == choice {
+                        res = Some(auth::open(&key, &sealed)?);
+                    }
+                }
+                res.ok_or(OTError::InvalidChoice(choice))
+            })
+            .collect()
+    }
+}
 
-        // ciphertexts.iter().for_each(|ciphertext| {
-        //     println!("ciphertexts len: {}", ciphertext.bytes_len());
-        // });
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> OTReceiver for CO15Receiver<C, P, E> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let mut res = self.receive_batch::<N, T, R>(&[choice], rng)?;
+        Ok(res.remove(0))
+    }
 
-        // decipher the choice ciphertext
-        let dest = ciphertexts[choice].clone();
-        let res = dest.decrypt(&key.into());
-        // TODO: handle the result
+    /// Send all R to sender in one message, and receive all the ciphertexts at once.
+    fn receive_batch<const N: usize, T, R>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<T>>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let keys = self.send_choices::<N, R>(choices, rng)?;
 
-        Ok(res)
+        let mut bytes = vec![0u8; self.encryption.ciphertext_len::<T>()];
+        choices
+            .iter()
+            .zip(keys)
+            .map(|(&choice, key)| {
+                let mut res = None;
+                for i in 0..N {
+                    self.channel.read_bytes(&mut bytes)?;
+                    // decipher the choice ciphertext
+                    if i == choice {
+                        res = Some(self.encryption.decrypt(&bytes, &key)?);
+                    }
+                }
+                res.ok_or(OTError::InvalidChoice(choice))
+            })
+            .collect()
     }
 }
 
-impl<C: AbstractChannel> ROTReceiver for CO15Receiver<C> {
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> ROTReceiver for CO15Receiver<C, P, E> {
     fn receive_random<const N: usize, T: Block, R: Rng>(
         &mut self,
         choice: usize,
         rng: &mut R,
     ) -> OTResult<T> {
-        // sample x from Z_p for N times
+        if choice >= N {
+            return Err(OTError::InvalidChoice(choice));
+        }
+
+        // sample x from Z_p
         // Compute R = cS + xB
         // where c is a choice
-        let x = Zp::rand(rng);
-        let b = EdwardsConfig::GENERATOR;
-        let r = self.s * Zp::from(choice as u32) + b * x;
-        self.channel.write_g(r)?;
+        let (r, key) = self.choose(choice, P::ScalarField::rand(rng))?;
+        E::write(&mut self.channel, &r)?;
         self.channel.flush()?;
 
-        let k = self.s * x;
-
-        // calculate key
-        let mut r_buff = Vec::new();
-        let mut s_buff = Vec::new();
-        let mut k_buff = Vec::new();
-
-        r.serialize_compressed(&mut r_buff)?;
-        self.s.serialize_compressed(&mut s_buff)?;
-        k.serialize_compressed(&mut k_buff)?;
-
-        let mut hasher = Keccak256::default();
-        hasher.update(s_buff);
-        hasher.update(r_buff);
-        hasher.update(k_buff);
-
-        let key = hasher.finalize();
         Ok(T::from_bytes(&key))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        io::{BufReader, BufWriter},
-        os::unix::net::UnixStream,
-        thread,
-    };
+    use std::thread;
 
     use rand::prelude::{thread_rng, ThreadRng};
 
     use super::*;
-    use crate::{channel::Channel, ot::OTError};
+    use crate::{
+        channel::MemChannel,
+        ot::{encoding::Compressed, OTError},
+    };
 
     use ark_ec::twisted_edwards::TECurveConfig;
     use ark_ed25519::EdwardsConfig;
@@ -238,14 +346,11 @@ mod tests {
 
     #[test]
     fn test_ot_block128() -> Result<(), Box<dyn std::error::Error>> {
-        let (sender, receiver) = UnixStream::pair().unwrap();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
 
         // Preapre receiver
         let receiver_handle = thread::spawn(move || {
             let mut rng = thread_rng();
-            let reader = BufReader::new(receiver.try_clone().unwrap());
-            let writer = BufWriter::new(receiver);
-            let receiver_channel = Channel::new(reader, writer);
             let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
 
             let choice = 1;
@@ -254,9 +359,6 @@ mod tests {
 
         // Prepare sender
         let mut rng = thread_rng();
-        let reader = BufReader::new(sender.try_clone().unwrap());
-        let writer = BufWriter::new(sender);
-        let sender_channel = Channel::new(reader, writer);
         let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
         let values: [Block128; 2] = [Block128::from(1), Block128::from(100)];
         ot_sender.send(values)?;
@@ -272,14 +374,11 @@ mod tests {
     fn test_ot_block256() -> Result<(), Box<dyn std::error::Error>> {
         // 1 out-of 5 OT with points on G
 
-        let (sender, receiver) = UnixStream::pair().unwrap();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
 
         // Preapre receiver
         let receiver_handle = thread::spawn(move || {
             let mut rng = thread_rng();
-            let reader = BufReader::new(receiver.try_clone().unwrap());
-            let writer = BufWriter::new(receiver);
-            let receiver_channel = Channel::new(reader, writer);
             let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
 
             let choice = 3;
@@ -292,9 +391,6 @@ mod tests {
 
         // Prepare sender
         let mut rng = thread_rng();
-        let reader = BufReader::new(sender.try_clone().unwrap());
-        let writer = BufWriter::new(sender);
-        let sender_channel = Channel::new(reader, writer);
         let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
 
         let mut rng = thread_rng();
@@ -328,14 +424,11 @@ mod tests {
 
     #[test]
     fn test_ot_blocks() -> Result<(), Box<dyn std::error::Error>> {
-        let (sender, receiver) = UnixStream::pair().unwrap();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
 
         // Preapre receiver
         let receiver_handle = thread::spawn(move || {
             let mut rng = thread_rng();
-            let reader = BufReader::new(receiver.try_clone().unwrap());
-            let writer = BufWriter::new(receiver);
-            let receiver_channel = Channel::new(reader, writer);
             let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
 
             let choice = 0;
@@ -344,9 +437,6 @@ mod tests {
 
         // Prepare sender
         let mut rng = thread_rng();
-        let reader = BufReader::new(sender.try_clone().unwrap());
-        let writer = BufWriter::new(sender);
-        let sender_channel = Channel::new(reader, writer);
         let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
 
         let values: [[Block128; 2]; 2] = [
@@ -361,4 +451,186 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ot_batch() -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let choices = [0, 2, 1, 2, 0];
+
+        // Preapre receiver
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
+
+            ot_receiver.receive_batch::<3, Block128, ThreadRng>(&choices, &mut rng)
+        });
+
+        // Prepare sender
+        let mut rng = thread_rng();
+        let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
+        let values = (0..choices.len() as u128)
+            .map(|i| std::array::from_fn(|j| Block128::from(10 * i + j as u128)))
+            .collect::<Vec<[Block128; 3]>>();
+        ot_sender.send_batch(&values)?;
+
+        let receiver_result = receiver_handle.join().unwrap()?;
+        for ((v, c), r) in values.iter().zip(choices).zip(receiver_result) {
+            assert_eq!(v[c], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_point() {
+        let (mut sender_channel, receiver_channel) = MemChannel::pair();
+
+        // Malicious sender publishes the identity as S
+        let sender_handle = thread::spawn(move || {
+            sender_channel.write_bytes(&[0u8; 32]).unwrap();
+            sender_channel.flush().unwrap();
+        });
+        let result = CO15Receiver::setup(receiver_channel);
+        sender_handle.join().unwrap();
+        assert!(matches!(result, Err(OTError::InvalidPoint(_))));
+
+        // Malicious receiver replies the identity as R
+        let (sender_channel, mut receiver_channel) = MemChannel::pair();
+        let receiver_handle = thread::spawn(move || {
+            receiver_channel.read_bytes(&mut [0u8; 32]).unwrap();
+            receiver_channel.write_bytes(&[0u8; 32]).unwrap();
+            receiver_channel.flush().unwrap();
+        });
+        let mut rng = thread_rng();
+        let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
+        let result = ot_sender.send([Block128::from(1), Block128::from(2)]);
+        receiver_handle.join().unwrap();
+        assert!(matches!(result, Err(OTError::InvalidPoint(_))));
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        // Sender closes the connection before sending S
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        drop(sender_channel);
+        let result = CO15Receiver::setup(receiver_channel);
+        assert!(matches!(result, Err(OTError::PeerAbort)));
+
+        // Sender sends bytes which are not an encoding of a point
+        let (mut sender_channel, receiver_channel) = MemChannel::pair();
+        sender_channel.write_bytes(&[0xff; 32]).unwrap();
+        sender_channel.flush().unwrap();
+        let result = CO15Receiver::setup(receiver_channel);
+        assert!(matches!(result, Err(OTError::MalformedMessage(_))));
+
+        // Choice out of range is rejected before anything is sent
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let mut rng = thread_rng();
+        let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
+        let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
+        let result = ot_receiver.receive_batch::<2, Block128, ThreadRng>(&[0, 2], &mut rng);
+        assert!(matches!(result, Err(OTError::InvalidChoice(2))));
+        let result = ot_receiver.receive_bytes::<2, ThreadRng>(&[3], &mut rng);
+        assert!(matches!(result, Err(OTError::InvalidChoice(3))));
+
+        // the sender sees no R, not even the one of the valid choice
+        drop(ot_receiver);
+        let result = ot_sender.channel.read_bytes(&mut [0u8; 1]);
+        assert!(matches!(
+            result.map_err(OTError::from),
+            Err(OTError::PeerAbort)
+        ));
+    }
+
+    #[test]
+    fn test_ot_authenticated() -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let choices = [1, 0, 2];
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_receiver =
+                CO15Receiver::setup(receiver_channel)?.with_encryption(Encryption::Authenticated);
+
+            let blocks = ot_receiver.receive_batch::<3, Block256, ThreadRng>(&choices, &mut rng)?;
+            let bytes = ot_receiver.receive_bytes::<3, ThreadRng>(&choices, &mut rng)?;
+            Ok::<_, OTError>((blocks, bytes))
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_sender =
+            CO15Sender::setup(sender_channel, &mut rng)?.with_encryption(Encryption::Authenticated);
+        let blocks = (0..choices.len())
+            .map(|i| std::array::from_fn(|j| Block256::from([(10 * i + j) as u8; 32])))
+            .collect::<Vec<[Block256; 3]>>();
+        let bytes = (0..choices.len())
+            .map(|i| std::array::from_fn(|j| vec![j as u8; 10 * i + j]))
+            .collect::<Vec<[Vec<u8>; 3]>>();
+        ot_sender.send_batch(&blocks)?;
+        ot_sender.send_bytes(&bytes)?;
+
+        let (received_blocks, received_bytes) = receiver_handle.join().unwrap()?;
+        for (i, c) in choices.into_iter().enumerate() {
+            assert_eq!(received_blocks[i], blocks[i][c]);
+            assert_eq!(received_bytes[i], bytes[i][c]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ot_tampered() {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        // Sender encrypts the messages with a key which is not derived from R
+        let sender_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
+            ot_sender.channel.read_bytes(&mut [0u8; 32]).unwrap();
+            for m in [b"zero", b"one!"] {
+                auth::write_sealed(&mut ot_sender.channel, &[0u8; 32], m).unwrap();
+            }
+            ot_sender.channel.flush().unwrap();
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
+        let result = ot_receiver.receive_bytes::<2, ThreadRng>(&[1], &mut rng);
+        sender_handle.join().unwrap();
+        assert!(matches!(result, Err(OTError::DecryptionFailed)));
+    }
+
+    fn run_ot_with_group<P: CurveGroup, E: GroupEncoding<P> + 'static>(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let choices = [1, 0, 1];
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_receiver = CO15Receiver::<_, P, E>::setup_with_group(receiver_channel)?;
+            ot_receiver.receive_batch::<2, Block128, ThreadRng>(&choices, &mut rng)
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_sender = CO15Sender::<_, P, E>::setup_with_group(sender_channel, &mut rng)?;
+        let values = (0..choices.len() as u128)
+            .map(|i| [Block128::from(2 * i), Block128::from(2 * i + 1)])
+            .collect::<Vec<_>>();
+        ot_sender.send_batch(&values)?;
+
+        let receiver_result = receiver_handle.join().unwrap()?;
+        for ((v, c), r) in values.iter().zip(choices).zip(receiver_result) {
+            assert_eq!(v[c], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ot_generic_group() -> Result<(), Box<dyn std::error::Error>> {
+        run_ot_with_group::<ark_bls12_381::G1Projective, Compressed>()?;
+        run_ot_with_group::<ark_secp256k1::Projective, Compressed>()?;
+        run_ot_with_group::<G, Compressed>()?;
+        run_ot_with_group::<G, Ristretto>()
+    }
 }
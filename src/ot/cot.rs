@@ -0,0 +1,232 @@
+//! Adapters turning correlated OT into random OT and chosen-message OT.
+//!
+//! Given k_i and Δ, the sender derives random OT keys H(i, k_i) and H(i, k_i ^ Δ).
+//! The receiver holding k_i ^ b_i * Δ derives H(i, k_i ^ b_i * Δ), which is the key of its choice.
+use ark_std::rand::Rng;
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    block::{Block, Block128},
+    channel::AbstractChannel,
+};
+
+use super::{
+    check_arity, COTReceiver, COTSender, OTError, OTReceiver, OTResult, OTSender, ROTReceiver,
+    ROTSender,
+};
+
+/// Break the correlation of the j-th COT output
+fn hash_cot(j: u64, block: Block128) -> [u8; 32] {
+    let mut hasher = Keccak256::default();
+    hasher.update(j.to_le_bytes());
+    hasher.update(block.as_bytes());
+    hasher.finalize().into()
+}
+
+/// OT sender built on top of correlated OT sender
+pub struct CotOTSender<S: COTSender, C: AbstractChannel> {
+    cot: S,
+    channel: C,
+    counter: u64,
+}
+
+impl<S: COTSender, C: AbstractChannel> CotOTSender<S, C> {
+    pub fn new(cot: S, channel: C) -> Self {
+        Self {
+            cot,
+            channel,
+            counter: 0,
+        }
+    }
+
+    /// Generate n random OT key pairs
+    fn keys(&mut self, n: usize) -> OTResult<Vec<[[u8; 32]; 2]>> {
+        let delta = self.cot.delta();
+        let keys = self
+            .cot
+            .send_correlated(n)?
+            .into_iter()
+            .map(|k| {
+                let j = self.counter;
+                self.counter += 1;
+                [hash_cot(j, k), hash_cot(j, k ^ delta)]
+            })
+            .collect();
+        Ok(keys)
+    }
+
+    /// Generate n random OTs at once
+    pub fn send_random_batch<T: Block>(&mut self, n: usize) -> OTResult<Vec<[T; 2]>> {
+        let keys = self.keys(n)?;
+        Ok(keys
+            .iter()
+            .map(|k| [T::from_bytes(&k[0]), T::from_bytes(&k[1])])
+            .collect())
+    }
+}
+
+impl<S: COTSender, C: AbstractChannel> OTSender for CotOTSender<S, C> {
+    fn send<const N: usize, T>(&mut self, values: [T; N]) -> OTResult<()>
+    where
+        T: Block + Clone,
+    {
+        check_arity::<N>()?;
+        let keys = self.keys(1)?;
+        for (v, key) in values.iter().zip(keys[0]) {
+            self.channel.write_bytes(&v.encrypt(&key).as_bytes())?;
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<S: COTSender, C: AbstractChannel> ROTSender for CotOTSender<S, C> {
+    fn send_random<const N: usize, T: Block>(&mut self) -> OTResult<[T; N]> {
+        check_arity::<N>()?;
+        let keys = self.keys(1)?;
+        Ok(std::array::from_fn(|i| T::from_bytes(&keys[0][i])))
+    }
+}
+
+/// OT receiver built on top of correlated OT receiver
+pub struct CotOTReceiver<R: COTReceiver, C: AbstractChannel> {
+    cot: R,
+    channel: C,
+    counter: u64,
+}
+
+impl<R: COTReceiver, C: AbstractChannel> CotOTReceiver<R, C> {
+    pub fn new(cot: R, channel: C) -> Self {
+        Self {
+            cot,
+            channel,
+            counter: 0,
+        }
+    }
+
+    /// Generate the key of the choice for each OT
+    fn keys(&mut self, choices: &[bool]) -> OTResult<Vec<[u8; 32]>> {
+        let keys = self
+            .cot
+            .receive_correlated(choices)?
+            .into_iter()
+            .map(|k| {
+                let j = self.counter;
+                self.counter += 1;
+                hash_cot(j, k)
+            })
+            .collect();
+        Ok(keys)
+    }
+
+    /// Generate random OTs for the choices at once
+    pub fn receive_random_batch<T: Block>(&mut self, choices: &[bool]) -> OTResult<Vec<T>> {
+        let keys = self.keys(choices)?;
+        Ok(keys.iter().map(|k| T::from_bytes(k)).collect())
+    }
+}
+
+fn choice_bit(choice: usize) -> OTResult<bool> {
+    match choice {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(OTError::InvalidChoice(choice)),
+    }
+}
+
+impl<Cot: COTReceiver, C: AbstractChannel> OTReceiver for CotOTReceiver<Cot, C> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, _rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        check_arity::<N>()?;
+        let keys = self.keys(&[choice_bit(choice)?])?;
+
+        let d = T::default();
+        let mut ciphertexts = [vec![0u8; d.bytes_len()], vec![0u8; d.bytes_len()]];
+        for c in ciphertexts.iter_mut() {
+            self.channel.read_bytes(c)?;
+        }
+
+        Ok(T::from_bytes(&ciphertexts[choice]).decrypt(&keys[0]))
+    }
+}
+
+impl<Cot: COTReceiver, C: AbstractChannel> ROTReceiver for CotOTReceiver<Cot, C> {
+    fn receive_random<const N: usize, T: Block, R: Rng>(
+        &mut self,
+        choice: usize,
+        _rng: &mut R,
+    ) -> OTResult<T> {
+        check_arity::<N>()?;
+        let keys = self.keys(&[choice_bit(choice)?])?;
+        Ok(T::from_bytes(&keys[0]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{
+        channel::MemChannel,
+        ot::{
+            co15::{CO15Receiver, CO15Sender},
+            extension::iknp::{IknpReceiver, IknpSender, SecurityLevel},
+        },
+    };
+
+    #[test]
+    fn test_cot_ot() -> Result<(), Box<dyn std::error::Error>> {
+        let choices = [1, 0, 0, 1];
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut base_ot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+            let cot = IknpReceiver::setup(
+                &mut base_ot,
+                ext_receiver_channel.clone(),
+                SecurityLevel::default(),
+                &mut rng,
+            )?;
+            let mut receiver = CotOTReceiver::new(cot, ext_receiver_channel);
+
+            let chosen = choices
+                .iter()
+                .map(|&c| receiver.receive::<2, Block128, ThreadRng>(c, &mut rng))
+                .collect::<OTResult<Vec<_>>>()?;
+            let random = receiver.receive_random_batch::<Block128>(&[true, false])?;
+            Ok::<_, OTError>((chosen, random))
+        });
+
+        let mut rng = thread_rng();
+        let mut base_ot = CO15Receiver::setup(ot_receiver_channel)?;
+        let cot = IknpSender::setup(
+            &mut base_ot,
+            ext_sender_channel.clone(),
+            SecurityLevel::default(),
+            &mut rng,
+        )?;
+        let mut sender = CotOTSender::new(cot, ext_sender_channel);
+
+        for i in 0..choices.len() as u128 {
+            sender.send([Block128::from(i), Block128::from(i + 10)])?;
+        }
+        let random = sender.send_random_batch::<Block128>(2)?;
+
+        let (received_chosen, received_random) = receiver_handle.join().unwrap()?;
+        for (i, (v, c)) in received_chosen.iter().zip(choices).enumerate() {
+            assert_eq!(*v, Block128::from(i as u128 + 10 * c as u128));
+        }
+        assert_eq!(received_random, vec![random[0][1], random[1][0]]);
+
+        Ok(())
+    }
+}
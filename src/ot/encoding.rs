@@ -0,0 +1,161 @@
+//! Encoding of group elements exchanged by the base OTs.
+//!
+//! Every point received from the peer goes through `GroupEncoding::read`, which rejects
+//! malformed encodings, the identity and points outside of the prime order group.
+use ark_ec::CurveGroup;
+use ark_serialize::Valid;
+
+use crate::{channel::AbstractChannel, ristretto, types::G};
+
+use super::{OTError, OTResult};
+
+pub trait GroupEncoding<P: CurveGroup> {
+    /// Length of an encoded point in bytes
+    fn encoded_len() -> usize;
+
+    fn encode(p: &P) -> OTResult<Vec<u8>>;
+
+    /// Decode and validate a point. The identity is rejected.
+    fn decode(bytes: &[u8]) -> OTResult<P>;
+
+    fn write<C: AbstractChannel>(channel: &mut C, p: &P) -> OTResult<()> {
+        channel.write_bytes(&Self::encode(p)?)?;
+        Ok(())
+    }
+
+    fn read<C: AbstractChannel>(channel: &mut C) -> OTResult<P> {
+        let mut bytes = vec![0u8; Self::encoded_len()];
+        channel.read_bytes(&mut bytes)?;
+        Self::decode(&bytes)
+    }
+}
+
+/// Check that a point is a valid element of the prime order group.
+///
+/// The identity makes every key a public constant, and points with a component in the
+/// small order subgroup (cofactor 8 on ed25519) leak the choice or key bits.
+/// The deserializer may already reject some of these, but the protocol must not
+/// depend on it.
+fn validate_point<P: CurveGroup>(p: &P) -> OTResult<()> {
+    if p.is_zero() {
+        return Err(OTError::InvalidPoint("identity"));
+    }
+    // checks that the point is on the curve and in the prime order subgroup
+    p.into_affine()
+        .check()
+        .map_err(|_| OTError::InvalidPoint("not in prime order subgroup"))
+}
+
+/// Compressed point serialization of arkworks, which works with any curve.
+/// The subgroup of a received point is checked explicitly.
+pub struct Compressed;
+
+impl<P: CurveGroup> GroupEncoding<P> for Compressed {
+    fn encoded_len() -> usize {
+        P::generator().compressed_size()
+    }
+
+    fn encode(p: &P) -> OTResult<Vec<u8>> {
+        let mut buff = Vec::new();
+        p.serialize_compressed(&mut buff)?;
+        Ok(buff)
+    }
+
+    fn decode(bytes: &[u8]) -> OTResult<P> {
+        let p = P::deserialize_compressed_unchecked(bytes)
+            .map_err(|e| OTError::MalformedMessage(e.to_string()))?;
+        validate_point(&p)?;
+        Ok(p)
+    }
+}
+
+/// Ristretto255 encoding of ed25519 points.
+/// Every encoding is canonical and represents an element of the prime order group,
+/// so there is no cofactor to take care of.
+pub struct Ristretto;
+
+impl GroupEncoding<G> for Ristretto {
+    fn encoded_len() -> usize {
+        ristretto::ENCODED_LEN
+    }
+
+    fn encode(p: &G) -> OTResult<Vec<u8>> {
+        Ok(ristretto::encode(p).to_vec())
+    }
+
+    fn decode(bytes: &[u8]) -> OTResult<G> {
+        let bytes: &[u8; ristretto::ENCODED_LEN] = bytes
+            .try_into()
+            .map_err(|_| OTError::MalformedMessage("invalid ristretto length".into()))?;
+        // the identity is the only element encoded as zeros
+        if bytes.iter().all(|&b| b == 0) {
+            return Err(OTError::InvalidPoint("identity"));
+        }
+        ristretto::decode(bytes)
+            .ok_or_else(|| OTError::MalformedMessage("invalid ristretto encoding".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::{twisted_edwards::TECurveConfig, Group};
+    use ark_ed25519::{EdwardsAffine, EdwardsConfig, Fq};
+    use ark_ff::{One, Zero};
+    use ark_std::UniformRand;
+    use rand::prelude::thread_rng;
+
+    use super::*;
+    use crate::types::Zp;
+
+    #[test]
+    fn test_validate_point() {
+        let mut rng = thread_rng();
+        let b = EdwardsConfig::GENERATOR;
+        assert!(validate_point(&(b * Zp::rand(&mut rng))).is_ok());
+
+        assert!(matches!(
+            validate_point(&G::zero()),
+            Err(OTError::InvalidPoint(_))
+        ));
+
+        // (0, -1) is the point of order 2
+        let torsion: G = EdwardsAffine::new_unchecked(Fq::zero(), -Fq::one()).into();
+        assert!(matches!(
+            validate_point(&torsion),
+            Err(OTError::InvalidPoint(_))
+        ));
+        assert!(matches!(
+            validate_point(&(torsion + b)),
+            Err(OTError::InvalidPoint(_))
+        ));
+
+        // Compressed decoding checks the subgroup even if the encoding is well formed
+        let bytes = <Compressed as GroupEncoding<G>>::encode(&(torsion + b)).unwrap();
+        assert!(matches!(
+            <Compressed as GroupEncoding<G>>::decode(&bytes),
+            Err(OTError::InvalidPoint(_))
+        ));
+    }
+
+    #[test]
+    fn test_ristretto_decode() {
+        let mut rng = thread_rng();
+        let p = G::generator() * Zp::rand(&mut rng);
+        let bytes = Ristretto::encode(&p).unwrap();
+        let decoded = Ristretto::decode(&bytes).unwrap();
+        assert_eq!(Ristretto::encode(&decoded).unwrap(), bytes);
+
+        assert!(matches!(
+            Ristretto::decode(&[0u8; 32]),
+            Err(OTError::InvalidPoint(_))
+        ));
+        assert!(matches!(
+            Ristretto::decode(&[0xff; 32]),
+            Err(OTError::MalformedMessage(_))
+        ));
+        assert!(matches!(
+            Ristretto::decode(&[1u8; 31]),
+            Err(OTError::MalformedMessage(_))
+        ));
+    }
+}
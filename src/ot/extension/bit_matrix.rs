@@ -0,0 +1,237 @@
+//! Bit-packed matrix used to represent t, u and q matrix of OT extension.
+//!
+//! Each row is stored as 128 bit words. j-th bit of a row lives in (j / 128)-th word,
+//! at bit (j % 128) of the word's u128 representation.
+use ark_std::rand::Rng;
+
+use crate::block::{Block, Block128};
+
+const WORD_BITS: usize = 128;
+
+/// Row-major matrix of bits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitMatrix {
+    rows: usize,
+    cols: usize,
+    words_per_row: usize,
+    data: Vec<Block128>,
+}
+
+/// Mask selecting bits whose index has the bit `w` unset
+const fn transpose_mask(w: usize) -> u128 {
+    let mut mask = 0u128;
+    let mut i = 0;
+    while i < WORD_BITS {
+        if i & w == 0 {
+            mask |= 1 << i;
+        }
+        i += 1;
+    }
+    mask
+}
+
+const MASKS: [(usize, u128); 7] = [
+    (64, transpose_mask(64)),
+    (32, transpose_mask(32)),
+    (16, transpose_mask(16)),
+    (8, transpose_mask(8)),
+    (4, transpose_mask(4)),
+    (2, transpose_mask(2)),
+    (1, transpose_mask(1)),
+];
+
+/// Transpose 128 x 128 bit matrix in place by recursively swapping off diagonal blocks
+fn transpose_tile(tile: &mut [u128; WORD_BITS]) {
+    for (w, mask) in MASKS {
+        for r in (0..WORD_BITS).filter(|r| r & w == 0) {
+            let t = ((tile[r] >> w) ^ tile[r + w]) & mask;
+            tile[r] ^= t << w;
+            tile[r + w] ^= t;
+        }
+    }
+}
+
+impl BitMatrix {
+    /// Create zero matrix of the given size
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words_per_row = cols.div_ceil(WORD_BITS);
+        Self {
+            rows,
+            cols,
+            words_per_row,
+            data: vec![Block128::default(); rows * words_per_row],
+        }
+    }
+
+    /// Create matrix filled with random bits
+    pub fn random<R: Rng>(rows: usize, cols: usize, rng: &mut R) -> Self {
+        let mut matrix = Self::new(rows, cols);
+        for i in 0..rows {
+            for word in matrix.row_mut(i) {
+                *word = Block128::from(rng.gen::<u128>());
+            }
+            matrix.clear_padding(i);
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Number of 128 bit words in a row
+    pub fn words_per_row(&self) -> usize {
+        self.words_per_row
+    }
+
+    pub fn row(&self, i: usize) -> &[Block128] {
+        &self.data[i * self.words_per_row..(i + 1) * self.words_per_row]
+    }
+
+    pub fn row_mut(&mut self, i: usize) -> &mut [Block128] {
+        &mut self.data[i * self.words_per_row..(i + 1) * self.words_per_row]
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> bool {
+        let word = u128::from(self.row(i)[j / WORD_BITS]);
+        (word >> (j % WORD_BITS)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, j: usize, bit: bool) {
+        let word = &mut self.row_mut(i)[j / WORD_BITS];
+        let mask = 1u128 << (j % WORD_BITS);
+        let value = if bit {
+            u128::from(*word) | mask
+        } else {
+            u128::from(*word) & !mask
+        };
+        *word = Block128::from(value);
+    }
+
+    /// XOR `other` into i-th row
+    pub fn xor_row(&mut self, i: usize, other: &[Block128]) {
+        self.row_mut(i)
+            .iter_mut()
+            .zip(other)
+            .for_each(|(w, o)| *w = *w ^ *o);
+    }
+
+    /// Extract j-th column packed into 128 bit words
+    pub fn column(&self, j: usize) -> Vec<Block128> {
+        let mut col = vec![0u128; self.rows.div_ceil(WORD_BITS)];
+        for i in 0..self.rows {
+            col[i / WORD_BITS] |= (self.get(i, j) as u128) << (i % WORD_BITS);
+        }
+        col.into_iter().map(Block128::from).collect()
+    }
+
+    /// Serialize i-th row into bytes. The length is `words_per_row * 16`.
+    pub fn row_bytes(&self, i: usize) -> Vec<u8> {
+        self.row(i).iter().flat_map(|w| w.as_bytes()).collect()
+    }
+
+    /// Set i-th row from bytes serialized by `row_bytes`.
+    /// Missing bytes are treated as zeros.
+    pub fn set_row_bytes(&mut self, i: usize, bytes: &[u8]) {
+        let words_per_row = self.words_per_row;
+        let row = self.row_mut(i);
+        for (k, word) in row.iter_mut().enumerate().take(words_per_row) {
+            let start = (k * 16).min(bytes.len());
+            let end = ((k + 1) * 16).min(bytes.len());
+            *word = Block128::from_bytes(&bytes[start..end]);
+        }
+        self.clear_padding(i);
+    }
+
+    /// Zero out the bits beyond `cols` in the last word of i-th row
+    fn clear_padding(&mut self, i: usize) {
+        let rem = self.cols % WORD_BITS;
+        if rem != 0 {
+            let last = self.words_per_row - 1;
+            let word = &mut self.row_mut(i)[last];
+            *word = Block128::from(u128::from(*word) & ((1u128 << rem) - 1));
+        }
+    }
+
+    /// Transpose the matrix 128 x 128 tile at a time
+    pub fn transpose(&self) -> Self {
+        let mut out = Self::new(self.cols, self.rows);
+        let mut tile = [0u128; WORD_BITS];
+
+        for rb in 0..self.rows.div_ceil(WORD_BITS) {
+            for cb in 0..self.words_per_row {
+                for (k, t) in tile.iter_mut().enumerate() {
+                    let i = rb * WORD_BITS + k;
+                    *t = if i < self.rows {
+                        u128::from(self.data[i * self.words_per_row + cb])
+                    } else {
+                        0
+                    };
+                }
+
+                transpose_tile(&mut tile);
+
+                for (k, t) in tile.iter().enumerate() {
+                    let i = cb * WORD_BITS + k;
+                    if i < out.rows {
+                        out.data[i * out.words_per_row + rb] = Block128::from(*t);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_transpose() {
+        let mut rng = thread_rng();
+        for (rows, cols) in [(128, 128), (128, 1000), (192, 300), (7, 5)] {
+            let matrix = BitMatrix::random(rows, cols, &mut rng);
+            let transposed = matrix.transpose();
+
+            assert_eq!(transposed.rows(), cols);
+            assert_eq!(transposed.cols(), rows);
+            for i in 0..rows {
+                for j in 0..cols {
+                    assert_eq!(matrix.get(i, j), transposed.get(j, i));
+                }
+            }
+            assert_eq!(transposed.transpose(), matrix);
+        }
+    }
+
+    #[test]
+    fn test_row_ops() {
+        let mut rng = thread_rng();
+        let mut matrix = BitMatrix::random(3, 200, &mut rng);
+        let other = BitMatrix::random(1, 200, &mut rng);
+
+        let expected = (0..200)
+            .map(|j| matrix.get(1, j) ^ other.get(0, j))
+            .collect::<Vec<_>>();
+        matrix.xor_row(1, other.row(0));
+        assert!((0..200).all(|j| matrix.get(1, j) == expected[j]));
+
+        let bytes = matrix.row_bytes(2);
+        let mut copied = BitMatrix::new(1, 200);
+        copied.set_row_bytes(0, &bytes);
+        assert_eq!(copied.row(0), matrix.row(2));
+
+        matrix.set(0, 150, true);
+        let col = matrix.column(150);
+        assert_eq!(u128::from(col[0]) & 1, 1);
+        assert_eq!(col.len(), 1);
+    }
+}
@@ -0,0 +1,425 @@
+//! Implements Ferret silent OT extension with regular noise
+//! Refer: https://eprint.iacr.org/2020/924.pdf
+//!
+//! Each iteration turns k + t * h correlated OTs into n correlated OTs.
+//! 1. t single point COTs of length l = 2^h are generated from GGM trees, which gives
+//!    sparse correlation w = v ^ e * Δ where e has exactly one non zero entry in each block.
+//! 2. Primal LPN with a local linear code A mixes k reserved COTs into the sparse correlation,
+//!    so that the outputs are z = v ^ k * A and z ^ r * Δ where r = e ^ x * A is pseudo random.
+//!
+//! The first k + t * h outputs are reserved to bootstrap the next iteration.
+use std::ops::BitXor;
+
+use ark_std::rand::Rng;
+use rand::{prelude::thread_rng, RngCore};
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    block::{Block, Block128},
+    channel::AbstractChannel,
+    ot::{COTReceiver, COTSender, OTError, OTResult},
+    prg::Prg,
+};
+
+use super::ggm;
+
+/// Number of non zero entries in each column of LPN matrix
+const D: usize = 10;
+/// Public seed of the local linear code
+const LPN_SEED: u128 = 0x4665_7272_6574_4c50_4e53_6565_6430;
+
+/// Parameters of one Ferret iteration. n = t * 2^h.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FerretParams {
+    /// Length of LPN secret
+    pub k: usize,
+    /// Number of noise, which is also the number of GGM trees
+    pub t: usize,
+    /// Depth of each GGM tree
+    pub h: usize,
+}
+
+impl Default for FerretParams {
+    /// Regular noise parameter from the Ferret paper, which outputs about 10^7 COTs per iteration
+    fn default() -> Self {
+        Self {
+            k: 589_760,
+            t: 1_319,
+            h: 13,
+        }
+    }
+}
+
+impl FerretParams {
+    /// Number of COTs generated in each iteration
+    pub fn n(&self) -> usize {
+        self.t << self.h
+    }
+
+    /// Number of COTs consumed in each iteration
+    pub fn reserved(&self) -> usize {
+        self.k + self.t * self.h
+    }
+
+    /// Check that an iteration outputs more COTs than it consumes, without overflow
+    fn check(&self) -> OTResult<()> {
+        if self.k == 0 || self.t == 0 || self.h == 0 {
+            return Err(OTError::InvalidParameter(format!(
+                "Ferret parameters must be positive, got {self:?}"
+            )));
+        }
+        let n = (self.h < usize::BITS as usize)
+            .then(|| 1usize << self.h)
+            .and_then(|l| self.t.checked_mul(l));
+        let reserved = self
+            .t
+            .checked_mul(self.h)
+            .and_then(|th| th.checked_add(self.k));
+        match (n, reserved) {
+            (Some(n), Some(reserved)) if reserved < n => Ok(()),
+            _ => Err(OTError::InvalidParameter(format!(
+                "Ferret iteration must output more than k + t * h COTs, got {self:?}"
+            ))),
+        }
+    }
+}
+
+/// Mask used to transfer GGM level sums with correlated OT
+fn hash_mask(tweak: u64, block: Block128) -> Block128 {
+    let mut hasher = Keccak256::default();
+    hasher.update(tweak.to_le_bytes());
+    hasher.update(block.as_bytes());
+    Block128::from_bytes(&hasher.finalize())
+}
+
+/// Apply the local linear code: out_j ^= XOR_{i in A_j} keys_i
+fn lpn_encode<T: Copy + BitXor<Output = T>>(out: &mut [T], keys: &[T]) {
+    let mut prg = Prg::new(Block128::from(LPN_SEED));
+    let k = keys.len() as u64;
+    for o in out.iter_mut() {
+        for _ in 0..D {
+            let i = (prg.next_u64() % k) as usize;
+            *o = *o ^ keys[i];
+        }
+    }
+}
+
+fn pack_bits(bits: impl Iterator<Item = bool>, len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len.div_ceil(8)];
+    for (i, b) in bits.enumerate() {
+        bytes[i / 8] |= (b as u8) << (i % 8);
+    }
+    bytes
+}
+
+fn unpack_bit(bytes: &[u8], i: usize) -> bool {
+    (bytes[i / 8] >> (i % 8)) & 1 == 1
+}
+
+pub struct FerretSender<C: AbstractChannel> {
+    channel: C,
+    params: FerretParams,
+    delta: Block128,
+    // keys of the COTs reserved for the next iteration
+    reserved: Vec<Block128>,
+    pool: Vec<Block128>,
+    pos: usize,
+    // number of COTs used for GGM trees so far, used as a tweak of the hash
+    counter: u64,
+}
+
+impl<C: AbstractChannel> FerretSender<C> {
+    /// Bootstrap Ferret with k + t * h COTs from the base COT sender.
+    /// Δ is inherited from the base COT sender.
+    pub fn setup<S: COTSender>(base: &mut S, channel: C, params: FerretParams) -> OTResult<Self> {
+        params.check()?;
+        let reserved = base.send_correlated(params.reserved())?;
+        Ok(Self {
+            channel,
+            params,
+            delta: base.delta(),
+            reserved,
+            pool: vec![],
+            pos: 0,
+            counter: 0,
+        })
+    }
+
+    /// Run one iteration. Returns n - k - t * h random COT keys.
+    pub fn extend(&mut self) -> OTResult<Vec<Block128>> {
+        let FerretParams { k, t, h } = self.params;
+        let (lpn_keys, tree_keys) = self.reserved.split_at(k);
+
+        // Choice corrections d = r ^ b for the COTs used for GGM trees
+        let mut d_bytes = vec![0u8; (t * h).div_ceil(8)];
+        self.channel.read_bytes(&mut d_bytes)?;
+
+        let mut rng = thread_rng();
+        let mut v = Vec::with_capacity(self.params.n());
+        for tree in 0..t {
+            // Expand GGM tree and sum up left and right nodes of each level
+            let (leaves, sums) = ggm::tree(Block128::from(rng.gen::<u128>()), h);
+            for (level, sum) in sums.iter().enumerate() {
+                let idx = tree * h + level;
+                let q = if unpack_bit(&d_bytes, idx) {
+                    tree_keys[idx] ^ self.delta
+                } else {
+                    tree_keys[idx]
+                };
+                let tweak = self.counter + idx as u64;
+                let c0 = sum[0] ^ hash_mask(tweak, q);
+                let c1 = sum[1] ^ hash_mask(tweak, q ^ self.delta);
+                self.channel.write_bytes(&c0.as_bytes())?;
+                self.channel.write_bytes(&c1.as_bytes())?;
+            }
+
+            // c = Δ ^ XOR of all leaves, to recover the punctured leaf
+            let c = leaves.iter().fold(self.delta, |acc, n| acc ^ *n);
+            self.channel.write_bytes(&c.as_bytes())?;
+            v.extend(leaves);
+        }
+        self.channel.flush()?;
+        self.counter += (t * h) as u64;
+
+        lpn_encode(&mut v, lpn_keys);
+
+        let output = v.split_off(self.params.reserved());
+        self.reserved = v;
+        Ok(output)
+    }
+}
+
+impl<C: AbstractChannel> COTSender for FerretSender<C> {
+    fn delta(&self) -> Block128 {
+        self.delta
+    }
+
+    fn send_correlated(&mut self, n: usize) -> OTResult<Vec<Block128>> {
+        let mut keys = Vec::with_capacity(n);
+        while keys.len() < n {
+            if self.pos == self.pool.len() {
+                self.pool = self.extend()?;
+                self.pos = 0;
+            }
+            let take = (n - keys.len()).min(self.pool.len() - self.pos);
+            keys.extend_from_slice(&self.pool[self.pos..self.pos + take]);
+            self.pos += take;
+        }
+
+        // Derandomize to the receiver's choices
+        let mut d_bytes = vec![0u8; n.div_ceil(8)];
+        self.channel.read_bytes(&mut d_bytes)?;
+        for (i, k) in keys.iter_mut().enumerate() {
+            if unpack_bit(&d_bytes, i) {
+                *k = *k ^ self.delta;
+            }
+        }
+
+        Ok(keys)
+    }
+}
+
+pub struct FerretReceiver<C: AbstractChannel> {
+    channel: C,
+    params: FerretParams,
+    // choice bits and keys of the COTs reserved for the next iteration
+    reserved: Vec<(bool, Block128)>,
+    pool: Vec<(bool, Block128)>,
+    pos: usize,
+    counter: u64,
+}
+
+impl<C: AbstractChannel> FerretReceiver<C> {
+    /// Bootstrap Ferret with k + t * h COTs with random choices from the base COT receiver
+    pub fn setup<R: COTReceiver, G: Rng>(
+        base: &mut R,
+        channel: C,
+        params: FerretParams,
+        rng: &mut G,
+    ) -> OTResult<Self> {
+        params.check()?;
+        let choices = (0..params.reserved())
+            .map(|_| rng.gen::<bool>())
+            .collect::<Vec<_>>();
+        let keys = base.receive_correlated(&choices)?;
+        Ok(Self {
+            channel,
+            params,
+            reserved: choices.into_iter().zip(keys).collect(),
+            pool: vec![],
+            pos: 0,
+            counter: 0,
+        })
+    }
+
+    /// Run one iteration. Returns n - k - t * h random choice bits and keys.
+    pub fn extend(&mut self) -> OTResult<Vec<(bool, Block128)>> {
+        let FerretParams { k, t, h } = self.params;
+        let l = 1 << h;
+        let (lpn_keys, tree_keys) = self.reserved.split_at(k);
+
+        // Sample noise position α of each tree.
+        // Choice of each level is the side of the sibling of the path to α.
+        let mut rng = thread_rng();
+        let alphas = (0..t).map(|_| rng.gen_range(0..l)).collect::<Vec<usize>>();
+        let d_bytes = pack_bits(
+            (0..t * h).map(|idx| tree_keys[idx].0 ^ ggm::sibling_bit(alphas[idx / h], h, idx % h)),
+            t * h,
+        );
+        self.channel.write_bytes(&d_bytes)?;
+        self.channel.flush()?;
+
+        let mut w = Vec::with_capacity(self.params.n());
+        let mut e = Vec::with_capacity(self.params.n());
+        for (tree, &alpha) in alphas.iter().enumerate() {
+            // Receive level sums of the sibling side and reconstruct all the leaves except α
+            let sums = (0..h)
+                .map(|level| {
+                    let idx = tree * h + level;
+                    let mut c = [[0u8; 16]; 2];
+                    self.channel.read_bytes(&mut c[0])?;
+                    self.channel.read_bytes(&mut c[1])?;
+
+                    let b = ggm::sibling_bit(alpha, h, level);
+                    let tweak = self.counter + idx as u64;
+                    Ok(Block128::from(c[b as usize]) ^ hash_mask(tweak, tree_keys[idx].1))
+                })
+                .collect::<OTResult<Vec<_>>>()?;
+            let mut nodes = ggm::punctured_tree(alpha, h, &sums);
+
+            let mut c = [0u8; 16];
+            self.channel.read_bytes(&mut c)?;
+            nodes[alpha] = nodes
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != alpha)
+                .fold(Block128::from(c), |acc, (_, n)| acc ^ *n);
+
+            w.extend(nodes);
+            e.extend((0..l).map(|i| i == alpha));
+        }
+        self.counter += (t * h) as u64;
+
+        // r = e ^ x * A
+        let (lpn_choices, lpn_keys): (Vec<_>, Vec<_>) = lpn_keys.iter().copied().unzip();
+        lpn_encode(&mut w, &lpn_keys);
+        lpn_encode(&mut e, &lpn_choices);
+
+        let mut cots = e.into_iter().zip(w).collect::<Vec<_>>();
+        let output = cots.split_off(self.params.reserved());
+        self.reserved = cots;
+        Ok(output)
+    }
+}
+
+impl<C: AbstractChannel> COTReceiver for FerretReceiver<C> {
+    fn receive_correlated(&mut self, choices: &[bool]) -> OTResult<Vec<Block128>> {
+        let n = choices.len();
+        let mut cots = Vec::with_capacity(n);
+        while cots.len() < n {
+            if self.pos == self.pool.len() {
+                self.pool = self.extend()?;
+                self.pos = 0;
+            }
+            let take = (n - cots.len()).min(self.pool.len() - self.pos);
+            cots.extend_from_slice(&self.pool[self.pos..self.pos + take]);
+            self.pos += take;
+        }
+
+        // Derandomize to the given choices
+        let d_bytes = pack_bits(cots.iter().zip(choices).map(|((r, _), b)| r ^ b), n);
+        self.channel.write_bytes(&d_bytes)?;
+        self.channel.flush()?;
+
+        Ok(cots.into_iter().map(|(_, m)| m).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::thread_rng;
+
+    use super::*;
+    use crate::{
+        channel::MemChannel,
+        ot::{
+            co15::{CO15Receiver, CO15Sender},
+            extension::iknp::{IknpReceiver, IknpSender, SecurityLevel},
+        },
+    };
+
+    const PARAMS: FerretParams = FerretParams { k: 256, t: 8, h: 7 };
+
+    #[test]
+    fn test_ferret_cot() -> Result<(), Box<dyn std::error::Error>> {
+        // More than one iteration
+        let n = 2000;
+        let choices = (0..n).map(|i| i % 7 == 0).collect::<Vec<_>>();
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+
+        let receiver_choices = choices.clone();
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut base_ot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+            let mut iknp = IknpReceiver::setup(
+                &mut base_ot,
+                ext_receiver_channel.clone(),
+                SecurityLevel::default(),
+                &mut rng,
+            )?;
+            let mut receiver =
+                FerretReceiver::setup(&mut iknp, ext_receiver_channel, PARAMS, &mut rng)?;
+            receiver.receive_correlated(&receiver_choices)
+        });
+
+        let mut rng = thread_rng();
+        let mut base_ot = CO15Receiver::setup(ot_receiver_channel)?;
+        let mut iknp = IknpSender::setup(
+            &mut base_ot,
+            ext_sender_channel.clone(),
+            SecurityLevel::default(),
+            &mut rng,
+        )?;
+        let mut sender = FerretSender::setup(&mut iknp, ext_sender_channel, PARAMS)?;
+        let keys = sender.send_correlated(n)?;
+        let delta = sender.delta();
+        assert_eq!(delta, iknp.delta());
+
+        let received = receiver_handle.join().unwrap()?;
+        assert_eq!(received.len(), n);
+        for ((k, b), m) in keys.iter().zip(choices).zip(received) {
+            let expected = if b { *k ^ delta } else { *k };
+            assert_eq!(m, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_params() {
+        let params = FerretParams::default();
+        assert_eq!(params.n(), 10_805_248);
+        assert!(params.reserved() < params.n());
+        assert_eq!(PARAMS.n(), 1024);
+        assert!(params.check().is_ok());
+        assert!(PARAMS.check().is_ok());
+
+        for invalid in [
+            FerretParams { k: 0, ..PARAMS },
+            FerretParams { t: 0, ..PARAMS },
+            FerretParams { h: 0, ..PARAMS },
+            // reserved() > n()
+            FerretParams { k: 1024, ..PARAMS },
+            FerretParams { h: 64, ..PARAMS },
+            FerretParams {
+                t: usize::MAX,
+                ..PARAMS
+            },
+        ] {
+            assert!(matches!(invalid.check(), Err(OTError::InvalidParameter(_))));
+        }
+    }
+}
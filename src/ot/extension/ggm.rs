@@ -0,0 +1,89 @@
+//! GGM tree used to build punctured pseudo random functions.
+//!
+//! Children of i-th node of a level are (2i)-th and (2i + 1)-th nodes of the next level.
+use crate::{block::Block128, prg::Prg};
+
+/// Expand a node into left and right children
+pub(crate) fn expand(node: Block128) -> [Block128; 2] {
+    let mut prg = Prg::new(node);
+    [prg.gen_block(), prg.gen_block()]
+}
+
+/// Expand GGM tree of depth h from the seed.
+/// Returns 2^h leaves and the XOR of left and right nodes of each level.
+pub(crate) fn tree(seed: Block128, h: usize) -> (Vec<Block128>, Vec<[Block128; 2]>) {
+    let mut nodes = vec![seed];
+    let mut sums = Vec::with_capacity(h);
+    for _ in 0..h {
+        nodes = nodes.iter().flat_map(|n| expand(*n)).collect();
+        let mut sum = [Block128::default(); 2];
+        for (i, node) in nodes.iter().enumerate() {
+            sum[i & 1] = sum[i & 1] ^ *node;
+        }
+        sums.push(sum);
+    }
+    (nodes, sums)
+}
+
+/// Side of the sibling of the path to α at the level, which is the negation of the bit of α.
+/// Bits of α are taken from the top of the tree.
+pub(crate) fn sibling_bit(alpha: usize, h: usize, level: usize) -> bool {
+    (alpha >> (h - 1 - level)) & 1 == 0
+}
+
+/// Reconstruct all the leaves except α from the level sums of the sibling side.
+/// Leaf α is set to zero.
+pub(crate) fn punctured_tree(alpha: usize, h: usize, sums: &[Block128]) -> Vec<Block128> {
+    let mut nodes = vec![Block128::default()];
+    for (level, sum) in sums.iter().enumerate().take(h) {
+        let b = sibling_bit(alpha, h, level) as usize;
+
+        // parent on the path to α is unknown
+        let path = alpha >> (h - level);
+        let mut children = Vec::with_capacity(nodes.len() * 2);
+        for (i, node) in nodes.iter().enumerate() {
+            if i == path {
+                children.extend([Block128::default(); 2]);
+            } else {
+                children.extend(expand(*node));
+            }
+        }
+
+        // sibling of the path = level sum ^ all the other known nodes on the same side
+        let sibling = 2 * path + b;
+        children[sibling] = children
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| i & 1 == b && *i != sibling)
+            .fold(*sum, |acc, (_, n)| acc ^ *n);
+        nodes = children;
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_punctured_tree() {
+        let h = 5;
+        let (leaves, sums) = tree(Block128::from(7), h);
+        assert_eq!(leaves.len(), 1 << h);
+
+        for alpha in [0, 13, (1 << h) - 1] {
+            let sibling_sums = (0..h)
+                .map(|level| sums[level][sibling_bit(alpha, h, level) as usize])
+                .collect::<Vec<_>>();
+            let punctured = punctured_tree(alpha, h, &sibling_sums);
+
+            for (i, (l, p)) in leaves.iter().zip(&punctured).enumerate() {
+                if i == alpha {
+                    assert_eq!(*p, Block128::default());
+                } else {
+                    assert_eq!(l, p);
+                }
+            }
+        }
+    }
+}
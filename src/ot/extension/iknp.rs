@@ -1,18 +1,66 @@
 //! Implements IKNP oblivious transfer extension
 //! Refer: https://www.iacr.org/archive/crypto2003/27290145/27290145.pdf
-use ark_std::rand::Rng;
-use rand::prelude::{thread_rng, ThreadRng};
+use rand::{
+    prelude::{thread_rng, ThreadRng},
+    Rng,
+};
 use sha3::{Digest, Keccak256};
 
 use crate::{
-    block::Block,
+    block::{Block, Block128},
     channel::AbstractChannel,
-    ot::{OTReceiver, OTResult, OTSender},
+    ot::{
+        auth::{self, Encryption},
+        COTReceiver, COTSender, OTError, OTReceiver, OTResult, OTSender,
+    },
+    prg::Prg,
 };
 
-/// Key size. Base of will be performed for K times to send M keys.
-const K: usize = 10;
+use super::bit_matrix::BitMatrix;
+
+/// Computational security parameter κ.
+/// Base OT will be performed for κ times to send M keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SecurityLevel {
+    #[default]
+    Bits128,
+    Bits192,
+    Bits256,
+}
+
+impl SecurityLevel {
+    /// Number of base OTs, which is also the bit length of each row of t, u and q matrix
+    pub fn kappa(&self) -> usize {
+        match self {
+            SecurityLevel::Bits128 => 128,
+            SecurityLevel::Bits192 => 192,
+            SecurityLevel::Bits256 => 256,
+        }
+    }
+}
+
+/// Derive key from j-th row of κ bits
+pub(crate) fn hash_row(j: usize, row: &[Block128]) -> [u8; 32] {
+    let mut hasher = Keccak256::default();
+    hasher.update((j as u64).to_le_bytes());
+    for word in row {
+        hasher.update(word.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Check that the M-bit columns fit in a carry block B
+fn check_carry_len<B: Block + Default, const M: usize>() -> OTResult<()> {
+    let bits = 8 * B::default().bytes_len();
+    if M > bits {
+        return Err(OTError::InvalidParameter(format!(
+            "{M} OTs do not fit in a carry block of {bits} bits"
+        )));
+    }
+    Ok(())
+}
 
+/// IKNP extension sender with the default security level
 // TODO: make this B generics cleaner
 pub fn ot_ext_send<
     Receiver: OTReceiver,
@@ -25,54 +73,55 @@ pub fn ot_ext_send<
     values: [[T; 2]; M],
     channel: &mut C,
 ) -> OTResult<()> {
-    // K OT for M bits messages where K is key length = 128.
+    ot_ext_send_with::<Receiver, T, B, M, C>(receiver, values, channel, SecurityLevel::default())
+}
+
+/// IKNP extension sender with the given security level
+pub fn ot_ext_send_with<
+    Receiver: OTReceiver,
+    T: Block,
+    B: Block + Clone + Default,
+    const M: usize,
+    C: AbstractChannel,
+>(
+    receiver: &mut Receiver,
+    values: [[T; 2]; M],
+    channel: &mut C,
+    security: SecurityLevel,
+) -> OTResult<()> {
+    check_carry_len::<B, M>()?;
+    // κ OT for M bits messages where κ is key length.
     // Ext sender acts as an OT receiver
-    // Sample K-bits
+    // Sample κ-bits
+    let kappa = security.kappa();
     let mut rng = thread_rng();
-    let s_choices = (0..K).map(|_| rng.gen::<bool>()).collect::<Vec<_>>();
-
-    // Perform K OT to receive K M-bits column
-    let mut q_matrix = vec![vec![false; K]; M];
-    for (i, s) in s_choices.iter().enumerate() {
-        let received = receiver.receive::<2, B, ThreadRng>(*s as usize, &mut rng)?;
-        // convert received block back to M bit vec
-        let bytes = &received.as_bytes()[0..(M / 8 + 1)];
+    let s_row = BitMatrix::random(1, kappa, &mut rng);
 
-        for j in 0..M {
-            let l = j / 8;
-            let byte = bytes[l];
-            q_matrix[j][i] = (byte >> (7 - j % 8) & 1) != 0;
-        }
+    // Perform κ OT to receive κ M-bits column
+    let choices = (0..kappa)
+        .map(|i| s_row.get(0, i) as usize)
+        .collect::<Vec<_>>();
+    let received = receiver.receive_batch::<2, B, ThreadRng>(&choices, &mut rng)?;
+    let mut q_matrix = BitMatrix::new(kappa, M);
+    for (i, row) in received.iter().enumerate() {
+        q_matrix.set_row_bytes(i, &row.as_bytes());
     }
+    // M * κ matrix where j-th row is q_j = t_j ^ (r_j * s)
+    let q_matrix = q_matrix.transpose();
 
     // compute M key pairs by hashing
-    let ciphertexts = q_matrix
+    let mut q_row_s = BitMatrix::new(1, kappa);
+    let ciphertexts = values
         .iter()
-        .zip(values)
-        .map(|(row, v)| {
-            let mut hasher_0 = Keccak256::default();
-            let mut k_0_bytes = vec![0u8; K];
-
-            for (i, &bit) in row.iter().enumerate() {
-                let byte = i / 8;
-                let shift = 7 - i % 8;
-                k_0_bytes[byte] |= (bit as u8) << shift;
-            }
-            hasher_0.update(k_0_bytes);
-            let k0 = hasher_0.finalize();
-            let v0 = v[0].encrypt(&k0.into()).as_bytes();
-
-            let mut hasher_1 = Keccak256::default();
-            let mut k_1_bytes = vec![0u8; K];
-            for (i, (&bit, s)) in row.iter().zip(&s_choices).enumerate() {
-                let bit = bit ^ s;
-                let byte = i / 8;
-                let shift = 7 - i % 8;
-                k_1_bytes[byte] |= (bit as u8) << shift;
-            }
-            hasher_1.update(k_1_bytes);
-            let k1 = hasher_1.finalize();
-            let v1 = v[1].encrypt(&k1.into()).as_bytes();
+        .enumerate()
+        .map(|(j, v)| {
+            let k0 = hash_row(j, q_matrix.row(j));
+            let v0 = v[0].encrypt(&k0).as_bytes();
+
+            q_row_s.row_mut(0).copy_from_slice(q_matrix.row(j));
+            q_row_s.xor_row(0, s_row.row(0));
+            let k1 = hash_row(j, q_row_s.row(0));
+            let v1 = v[1].encrypt(&k1).as_bytes();
 
             (v0, v1)
         })
@@ -89,13 +138,14 @@ pub fn ot_ext_send<
     Ok(())
 }
 
+/// IKNP extension receiver with the default security level.
 /// Const M is number of items to receive from sender
 /// T is a type of value to receive from sender
 /// B is a Block type to represent t, u, q matrix
 pub fn ot_ext_receive<
     Sender: OTSender,
     T: Block + Default + std::fmt::Debug,
-    B: Block + Clone,
+    B: Block + Clone + Default,
     const M: usize,
     C: AbstractChannel,
 >(
@@ -103,116 +153,394 @@ pub fn ot_ext_receive<
     choices: [bool; M],
     channel: &mut C,
 ) -> OTResult<[T; M]> {
-    // ) -> OTResult<()> {
-    // K OT for M-bits messages where K is key length = 128.
+    ot_ext_receive_with::<Sender, T, B, M, C>(sender, choices, channel, SecurityLevel::default())
+}
+
+/// IKNP extension receiver with the given security level
+pub fn ot_ext_receive_with<
+    Sender: OTSender,
+    T: Block + Default + std::fmt::Debug,
+    B: Block + Clone + Default,
+    const M: usize,
+    C: AbstractChannel,
+>(
+    sender: &mut Sender,
+    choices: [bool; M],
+    channel: &mut C,
+    security: SecurityLevel,
+) -> OTResult<[T; M]> {
+    check_carry_len::<B, M>()?;
+    // κ OT for M-bits messages where κ is key length.
     // Ext receiver acts as an OT sender
-    // 1. create M * K matrix where all the values for i'th row are choice bit b_i.
-    let b_matrix = choices.iter().map(|&b_i| vec![b_i; K]).collect::<Vec<_>>();
+    // 1. create 1 * M matrix of choice bits r.
+    let kappa = security.kappa();
+    let mut r_row = BitMatrix::new(1, M);
+    for (j, &b) in choices.iter().enumerate() {
+        r_row.set(0, j, b);
+    }
 
-    // 2. Sample random M * K matrix t_matrix to form share of b_matrix.
-    // t_matrix ^ u_matrix = b_matrix
+    // 2. Sample random κ * M matrix t_matrix, and compute u_matrix
+    // where each row of u_matrix is t_i ^ r
     let mut rng = thread_rng();
-    let t_matrix = (0..M)
-        .map(|_| {
-            let t_i: u128 = rng.gen();
-            let mut row = [false; K];
-            row.iter_mut()
-                .enumerate()
-                .take(K)
-                .map(|(i, _)| (t_i >> i) & 1 == 1)
-                .collect::<Vec<_>>()
-        })
-        .collect::<Vec<_>>();
+    let t_matrix = BitMatrix::random(kappa, M, &mut rng);
+    let mut u_matrix = t_matrix.clone();
+    for i in 0..kappa {
+        u_matrix.xor_row(i, r_row.row(0));
+    }
 
-    let u_matrix = b_matrix
-        .iter()
-        .zip(&t_matrix)
-        .map(|(b_row, t_row)| {
-            b_row
-                .iter()
-                .zip(t_row)
-                .map(|(b, t)| b ^ t)
-                .collect::<Vec<_>>()
+    // perform κ OT to send κ rows either (t_i, u_i)
+    // send M-bits as Vec<Block128>
+    let rows = (0..kappa)
+        .map(|i| {
+            [
+                B::from_bytes(&t_matrix.row_bytes(i)),
+                B::from_bytes(&u_matrix.row_bytes(i)),
+            ]
         })
         .collect::<Vec<_>>();
+    sender.send_batch(&rows)?;
+    let t_matrix = t_matrix.transpose();
 
-    // perform K OT to send K-columns either (t_col, u_col)
-    // send M-bits as Vec<Block128>
-    // For now, This OT happens sequentially.
-    // improve performance by making this OT paralelly
-    for i in 0..K {
-        let t_col = t_matrix.iter().map(|t_row| t_row[i]).collect::<Vec<_>>();
-        let mut t_bytes = vec![0u8; M / 8 + 1];
-        for (i, bit) in t_col.into_iter().enumerate() {
-            let byte = i / 8;
-            let shift = 7 - i % 8;
-            t_bytes[byte] |= (bit as u8) << shift;
-        }
-        let t_block = B::from_bytes(&t_bytes);
-
-        let u_col = u_matrix.iter().map(|u_row| u_row[i]).collect::<Vec<_>>();
-        let mut u_bytes = vec![0u8; M / 8 + 1];
-        for (i, bit) in u_col.into_iter().enumerate() {
-            let byte = i / 8;
-            let shift = 7 - i % 8;
-            u_bytes[byte] |= (bit as u8) << shift;
-        }
-        let u_block = B::from_bytes(&u_bytes);
-        sender.send([t_block, u_block])?;
-    }
-
-    let mut result: Vec<T> = vec![];
+    let mut result: [T; M] = std::array::from_fn(|_| T::default());
 
-    for (t_row, b) in t_matrix.iter().zip(choices) {
+    for (j, (b, res)) in choices.into_iter().zip(result.iter_mut()).enumerate() {
         // Receive M encrypted pair of values
         // decrypt the appropriate one
         // key is hash of t
-        let mut k_bytes = vec![0u8; K];
-        for (i, &bit) in t_row.iter().enumerate() {
-            let byte = i / 8;
-            let shift = 7 - i % 8;
-            k_bytes[byte] |= (bit as u8) << shift;
-        }
-
-        let mut hasher = Keccak256::default();
-        hasher.update(k_bytes);
-        let key = hasher.finalize();
+        let key = hash_row(j, t_matrix.row(j));
 
-        let d = T::default();
-        let mut v0_bytes = vec![0u8; d.bytes_len()];
-        let mut v1_bytes = vec![0u8; d.bytes_len()];
+        let mut v0_bytes = vec![0u8; res.bytes_len()];
+        let mut v1_bytes = vec![0u8; res.bytes_len()];
         channel.read_bytes(&mut v0_bytes)?;
         channel.read_bytes(&mut v1_bytes)?;
         if !b {
             // choice is 0-th element
             let v0_encrypted = T::from_bytes(&v0_bytes);
-            result.push(v0_encrypted.decrypt(&key.into()));
+            *res = v0_encrypted.decrypt(&key);
         } else {
             // choice is 1-st element
             let v1_encrypted = T::from_bytes(&v1_bytes);
-            result.push(v1_encrypted.decrypt(&key.into()));
+            *res = v1_encrypted.decrypt(&key);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Stateful IKNP extension sender.
+/// Base OTs are performed once in `setup`, and the received seeds are expanded with PRG
+/// to serve any number of extensions.
+pub struct IknpSender<C: AbstractChannel> {
+    channel: C,
+    kappa: usize,
+    // 1 * κ matrix of base OT choices s
+    s_row: BitMatrix,
+    // PRG seeded with k_i^{s_i}
+    prgs: Vec<Prg>,
+    // number of OTs extended so far, used as a tweak of the hash
+    counter: usize,
+    encryption: Encryption,
+}
+
+impl<C: AbstractChannel> IknpSender<C> {
+    /// Perform κ base OTs to receive seeds k_i^{s_i}.
+    /// Ext sender acts as an OT receiver
+    pub fn setup<OT: OTReceiver, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        security: SecurityLevel,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let kappa = security.kappa();
+        let s_row = BitMatrix::random(1, kappa, rng);
+
+        let choices = (0..kappa)
+            .map(|i| s_row.get(0, i) as usize)
+            .collect::<Vec<_>>();
+        let prgs = base_ot
+            .receive_batch::<2, Block128, R>(&choices, rng)?
+            .into_iter()
+            .map(Prg::new)
+            .collect();
+
+        Ok(Self {
+            channel,
+            kappa,
+            s_row,
+            prgs,
+            counter: 0,
+            encryption: Encryption::default(),
+        })
+    }
+
+    /// Set the encryption of the messages. The receiver has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// Receive u matrix and compute n * κ matrix where j-th row is q_j = t_j ^ (r_j * s)
+    fn extend_q(&mut self, n: usize) -> OTResult<BitMatrix> {
+        // q_i = PRG(k_i^{s_i}) ^ s_i * u_i
+        let mut q_matrix = BitMatrix::new(self.kappa, n);
+        let mut u_row = BitMatrix::new(1, n);
+        let mut u_bytes = vec![0u8; u_row.words_per_row() * 16];
+        for (i, prg) in self.prgs.iter_mut().enumerate() {
+            q_matrix
+                .row_mut(i)
+                .iter_mut()
+                .for_each(|w| *w = prg.gen_block());
+            self.channel.read_bytes(&mut u_bytes)?;
+            if self.s_row.get(0, i) {
+                u_row.set_row_bytes(0, &u_bytes);
+                q_matrix.xor_row(i, u_row.row(0));
+            }
+        }
+
+        Ok(q_matrix.transpose())
+    }
+
+    /// Extend n random OTs. Returns pair of keys for each OT.
+    pub fn extend(&mut self, n: usize) -> OTResult<Vec<[[u8; 32]; 2]>> {
+        let q_matrix = self.extend_q(n)?;
+
+        let mut q_row_s = BitMatrix::new(1, self.kappa);
+        let keys = (0..n)
+            .map(|j| {
+                let tweak = self.counter + j;
+                q_row_s.row_mut(0).copy_from_slice(q_matrix.row(j));
+                q_row_s.xor_row(0, self.s_row.row(0));
+                [
+                    hash_row(tweak, q_matrix.row(j)),
+                    hash_row(tweak, q_row_s.row(0)),
+                ]
+            })
+            .collect();
+        self.counter += n;
+
+        Ok(keys)
+    }
+
+    /// Send a pair of values for each OT
+    pub fn send<T: Block>(&mut self, values: &[[T; 2]]) -> OTResult<()> {
+        let keys = self.extend(values.len())?;
+        for (v, k) in values.iter().zip(keys) {
+            self.channel
+                .write_bytes(&self.encryption.encrypt(&v[0], &k[0]))?;
+            self.channel
+                .write_bytes(&self.encryption.encrypt(&v[1], &k[1]))?;
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+
+    /// Send a pair of variable length messages for each OT with authenticated encryption
+    pub fn send_bytes(&mut self, messages: &[[Vec<u8>; 2]]) -> OTResult<()> {
+        let keys = self.extend(messages.len())?;
+        for (m, k) in messages.iter().zip(keys) {
+            auth::write_sealed(&mut self.channel, &k[0], &m[0])?;
+            auth::write_sealed(&mut self.channel, &k[1], &m[1])?;
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Stateful IKNP extension receiver.
+/// Base OTs are performed once in `setup`, and the seed pairs are expanded with PRG
+/// to serve any number of extensions.
+pub struct IknpReceiver<C: AbstractChannel> {
+    channel: C,
+    kappa: usize,
+    // PRGs seeded with (k_i^0, k_i^1)
+    prgs: Vec<[Prg; 2]>,
+    counter: usize,
+    encryption: Encryption,
+}
+
+impl<C: AbstractChannel> IknpReceiver<C> {
+    /// Perform κ base OTs to send seed pairs (k_i^0, k_i^1).
+    /// Ext receiver acts as an OT sender
+    pub fn setup<OT: OTSender, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        security: SecurityLevel,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let kappa = security.kappa();
+        let seeds = (0..kappa)
+            .map(|_| {
+                [
+                    Block128::from(rng.gen::<u128>()),
+                    Block128::from(rng.gen::<u128>()),
+                ]
+            })
+            .collect::<Vec<_>>();
+        base_ot.send_batch(&seeds)?;
+        let prgs = seeds.into_iter().map(|seed| seed.map(Prg::new)).collect();
+
+        Ok(Self {
+            channel,
+            kappa,
+            prgs,
+            counter: 0,
+            encryption: Encryption::default(),
+        })
+    }
+
+    /// Set the encryption of the messages. The sender has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// Send u matrix for the choices and return n * κ matrix where j-th row is t_j
+    fn extend_t(&mut self, choices: &[bool]) -> OTResult<BitMatrix> {
+        let n = choices.len();
+        let mut r_row = BitMatrix::new(1, n);
+        for (j, &b) in choices.iter().enumerate() {
+            r_row.set(0, j, b);
+        }
+
+        // t_i = PRG(k_i^0), u_i = t_i ^ PRG(k_i^1) ^ r
+        let mut t_matrix = BitMatrix::new(self.kappa, n);
+        let mut u_row = BitMatrix::new(1, n);
+        for (i, [prg_0, prg_1]) in self.prgs.iter_mut().enumerate() {
+            t_matrix
+                .row_mut(i)
+                .iter_mut()
+                .for_each(|w| *w = prg_0.gen_block());
+            u_row
+                .row_mut(0)
+                .iter_mut()
+                .for_each(|w| *w = prg_1.gen_block());
+            u_row.xor_row(0, t_matrix.row(i));
+            u_row.xor_row(0, r_row.row(0));
+            self.channel.write_bytes(&u_row.row_bytes(0))?;
+        }
+        self.channel.flush()?;
+
+        Ok(t_matrix.transpose())
+    }
+
+    /// Extend OTs for the given choices. Returns the key of the choice for each OT.
+    pub fn extend(&mut self, choices: &[bool]) -> OTResult<Vec<[u8; 32]>> {
+        let n = choices.len();
+        let t_matrix = self.extend_t(choices)?;
+
+        let keys = (0..n)
+            .map(|j| hash_row(self.counter + j, t_matrix.row(j)))
+            .collect();
+        self.counter += n;
+
+        Ok(keys)
+    }
+
+    /// Receive the chosen value for each of the choices
+    pub fn receive<T: Block + Default>(&mut self, choices: &[bool]) -> OTResult<Vec<T>> {
+        let keys = self.extend(choices)?;
+
+        let len = self.encryption.ciphertext_len::<T>();
+        let mut v_bytes = [vec![0u8; len], vec![0u8; len]];
+        choices
+            .iter()
+            .zip(keys)
+            .map(|(&b, key)| {
+                self.channel.read_bytes(&mut v_bytes[0])?;
+                self.channel.read_bytes(&mut v_bytes[1])?;
+                self.encryption.decrypt(&v_bytes[b as usize], &key)
+            })
+            .collect()
+    }
+
+    /// Receive the chosen variable length message sent by `IknpSender::send_bytes`.
+    /// Returns `OTError::DecryptionFailed` if the chosen message fails authentication.
+    pub fn receive_bytes(&mut self, choices: &[bool]) -> OTResult<Vec<Vec<u8>>> {
+        let keys = self.extend(choices)?;
+
+        choices
+            .iter()
+            .zip(keys)
+            .map(|(&b, key)| {
+                let sealed = [
+                    auth::read_sealed(&mut self.channel)?,
+                    auth::read_sealed(&mut self.channel)?,
+                ];
+                auth::open(&key, &sealed[b as usize])
+            })
+            .collect()
+    }
+}
+
+/// IKNP with κ = 128 directly gives correlated OT where Δ = s, k_j = q_j and t_j = q_j ^ r_j * Δ
+impl<C: AbstractChannel> COTSender for IknpSender<C> {
+    fn delta(&self) -> Block128 {
+        self.s_row.row(0)[0]
+    }
+
+    fn send_correlated(&mut self, n: usize) -> OTResult<Vec<Block128>> {
+        if self.kappa != 128 {
+            return Err(OTError::UnsupportedSecurityLevel(self.kappa));
         }
+        let q_matrix = self.extend_q(n)?;
+        self.counter += n;
+        Ok((0..n).map(|j| q_matrix.row(j)[0]).collect())
     }
+}
 
-    let return_values = result.try_into().unwrap();
-    Ok(return_values)
+impl<C: AbstractChannel> COTReceiver for IknpReceiver<C> {
+    fn receive_correlated(&mut self, choices: &[bool]) -> OTResult<Vec<Block128>> {
+        if self.kappa != 128 {
+            return Err(OTError::UnsupportedSecurityLevel(self.kappa));
+        }
+        let t_matrix = self.extend_t(choices)?;
+        self.counter += choices.len();
+        Ok((0..choices.len()).map(|j| t_matrix.row(j)[0]).collect())
+    }
+}
+
+/// Heap-backed IKNP extension sender for the number of OTs chosen at runtime.
+/// Base OTs transfer 128 bit seeds which are expanded with PRG into M-bits columns,
+/// and the receiver sends u matrix over the channel.
+pub fn ot_ext_send_vec<Receiver: OTReceiver, T: Block, C: AbstractChannel>(
+    receiver: &mut Receiver,
+    values: &[[T; 2]],
+    channel: &mut C,
+    security: SecurityLevel,
+) -> OTResult<()> {
+    let mut sender = IknpSender::setup(receiver, channel.clone(), security, &mut thread_rng())?;
+    sender.send(values)
+}
+
+/// Heap-backed IKNP extension receiver for the number of OTs chosen at runtime.
+/// Returns the chosen value for each of the choices.
+pub fn ot_ext_receive_vec<Sender: OTSender, T: Block + Default, C: AbstractChannel>(
+    sender: &mut Sender,
+    choices: &[bool],
+    channel: &mut C,
+    security: SecurityLevel,
+) -> OTResult<Vec<T>> {
+    let mut receiver = IknpReceiver::setup(sender, channel.clone(), security, &mut thread_rng())?;
+    receiver.receive(choices)
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{
-        io::{BufReader, BufWriter},
-        os::unix::net::UnixStream,
-        thread,
-    };
+    use std::thread;
 
     use rand::prelude::thread_rng;
 
     use super::*;
     use crate::{
         block::*,
-        channel::Channel,
-        ot::co15::{CO15Receiver, CO15Sender},
+        channel::{MemChannel, MuxChannels},
+        ot::{
+            co15::{CO15Receiver, CO15Sender},
+            mr19::{MR19Receiver, MR19Sender},
+            np01::{NP01Receiver, NP01Sender},
+        },
     };
 
     const M: usize = 10;
@@ -221,48 +549,31 @@ mod tests {
     #[test]
     fn test_ot_extension() -> Result<(), Box<dyn std::error::Error>> {
         // Do 128 base OT for key exchange
-        let (ot_sender_stream, ot_receiver_stream) = UnixStream::pair().unwrap();
-        let (ext_sender_stream, ext_receiver_stream) = UnixStream::pair().unwrap();
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (mut ext_sender_channel, mut ext_receiver_channel) = MemChannel::pair();
         let choices: [bool; M] = std::array::from_fn(|i| i % 3 == 0);
 
         let receiver_handle = thread::spawn(move || {
             let mut rng = thread_rng();
-            let reader = BufReader::new(ot_sender_stream.try_clone().unwrap());
-            let writer = BufWriter::new(ot_sender_stream);
-            let sender_channel = Channel::new(reader, writer);
-
-            let mut ot_sender = CO15Sender::setup(sender_channel, &mut rng).unwrap();
-            let reader = BufReader::new(ext_receiver_stream.try_clone().unwrap());
-            let writer = BufWriter::new(ext_receiver_stream);
-            let mut ext_receiver_chan = Channel::new(reader, writer);
-
-            ot_ext_receive::<
-                CO15Sender<Channel<BufReader<UnixStream>, BufWriter<UnixStream>>>,
-                Block128,
-                CarryType,
-                M,
-                Channel<BufReader<UnixStream>, BufWriter<UnixStream>>,
-            >(&mut ot_sender, choices, &mut ext_receiver_chan)
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+
+            ot_ext_receive::<CO15Sender<MemChannel>, Block128, CarryType, M, MemChannel>(
+                &mut ot_sender,
+                choices,
+                &mut ext_receiver_channel,
+            )
         });
 
         // Prepare sender
-        let reader = BufReader::new(ot_receiver_stream.try_clone().unwrap());
-        let writer = BufWriter::new(ot_receiver_stream);
-        let receiver_channel = Channel::new(reader, writer);
-        let mut ot_receiver = CO15Receiver::setup(receiver_channel).unwrap();
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel).unwrap();
 
         let values = [[Block128::from(1), Block128::from(2)]; M];
-        let reader = BufReader::new(ext_sender_stream.try_clone().unwrap());
-        let writer = BufWriter::new(ext_sender_stream);
-        let mut ext_sender_chan = Channel::new(reader, writer);
-
-        ot_ext_send::<
-            CO15Receiver<Channel<BufReader<UnixStream>, BufWriter<UnixStream>>>,
-            Block128,
-            CarryType,
-            M,
-            Channel<BufReader<UnixStream>, BufWriter<UnixStream>>,
-        >(&mut ot_receiver, values, &mut ext_sender_chan)?;
+
+        ot_ext_send::<CO15Receiver<MemChannel>, Block128, CarryType, M, MemChannel>(
+            &mut ot_receiver,
+            values,
+            &mut ext_sender_channel,
+        )?;
 
         let receiver_result = receiver_handle.join().unwrap();
         assert!(receiver_result.is_ok());
@@ -279,4 +590,315 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_carry_len() {
+        assert!(check_carry_len::<CarryType, 128>().is_ok());
+        assert!(matches!(
+            check_carry_len::<CarryType, 129>(),
+            Err(OTError::InvalidParameter(_))
+        ));
+        assert!(check_carry_len::<[Block128; 8], 1000>().is_ok());
+    }
+
+    #[test]
+    fn test_ot_extension_256() -> Result<(), Box<dyn std::error::Error>> {
+        // Do 256 base OT for key exchange
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (mut ext_sender_channel, mut ext_receiver_channel) = MemChannel::pair();
+        let choices: [bool; M] = std::array::from_fn(|i| i % 2 == 0);
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+
+            ot_ext_receive_with::<_, Block256, CarryType, M, _>(
+                &mut ot_sender,
+                choices,
+                &mut ext_receiver_channel,
+                SecurityLevel::Bits256,
+            )
+        });
+
+        // Prepare sender
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel).unwrap();
+
+        let values = [[Block256::from([1u8; 32]), Block256::from([2u8; 32])]; M];
+
+        ot_ext_send_with::<_, Block256, CarryType, M, _>(
+            &mut ot_receiver,
+            values,
+            &mut ext_sender_channel,
+            SecurityLevel::Bits256,
+        )?;
+
+        let receiver_result = receiver_handle.join().unwrap()?;
+        let expected_result: [Block256; M] =
+            std::array::from_fn(|i| values[i][choices[i] as usize]);
+        assert_eq!(receiver_result, expected_result);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_security_level() {
+        assert_eq!(SecurityLevel::default().kappa(), 128);
+        assert_eq!(SecurityLevel::Bits192.kappa(), 192);
+        assert_eq!(SecurityLevel::Bits256.kappa(), 256);
+    }
+
+    #[test]
+    fn test_ot_extension_vec() -> Result<(), Box<dyn std::error::Error>> {
+        let m = 5000;
+        // base OTs and extension multiplexed over one connection
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let choices = (0..m).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let values = (0..m as u128)
+            .map(|i| [Block128::from(i), Block128::from(i + m as u128)])
+            .collect::<Vec<_>>();
+
+        let receiver_choices = choices.clone();
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mux = MuxChannels::new(receiver_channel);
+            let mut ot_sender = CO15Sender::setup(mux.channel(0), &mut rng).unwrap();
+
+            ot_ext_receive_vec::<_, Block128, _>(
+                &mut ot_sender,
+                &receiver_choices,
+                &mut mux.channel(1),
+                SecurityLevel::default(),
+            )
+        });
+
+        let mux = MuxChannels::new(sender_channel);
+        let mut ot_receiver = CO15Receiver::setup(mux.channel(0)).unwrap();
+
+        ot_ext_send_vec(
+            &mut ot_receiver,
+            &values,
+            &mut mux.channel(1),
+            SecurityLevel::default(),
+        )?;
+
+        let receiver_result = receiver_handle.join().unwrap()?;
+        assert_eq!(receiver_result.len(), m);
+        for ((v, c), r) in values.iter().zip(choices).zip(receiver_result) {
+            assert_eq!(v[c as usize], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ot_extension_reuse_base_ot() -> Result<(), Box<dyn std::error::Error>> {
+        let sizes = [100, 1000, 1];
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+
+            let mut receiver = IknpReceiver::setup(
+                &mut ot_sender,
+                ext_receiver_channel,
+                SecurityLevel::default(),
+                &mut rng,
+            )?;
+
+            sizes
+                .iter()
+                .map(|&n| {
+                    let choices = (0..n).map(|i| i % 2 == 1).collect::<Vec<_>>();
+                    receiver.receive::<Block128>(&choices)
+                })
+                .collect::<OTResult<Vec<_>>>()
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel).unwrap();
+
+        let mut sender = IknpSender::setup(
+            &mut ot_receiver,
+            ext_sender_channel,
+            SecurityLevel::default(),
+            &mut rng,
+        )?;
+
+        for n in sizes {
+            let values = (0..n as u128)
+                .map(|i| [Block128::from(i), Block128::from(i + 1)])
+                .collect::<Vec<_>>();
+            sender.send(&values)?;
+        }
+
+        let received = receiver_handle.join().unwrap()?;
+        for (n, result) in sizes.iter().zip(received) {
+            assert_eq!(result.len(), *n);
+            for (i, v) in result.iter().enumerate() {
+                assert_eq!(*v, Block128::from(i as u128 + (i % 2) as u128));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_correlated_ot() -> Result<(), Box<dyn std::error::Error>> {
+        let n = 300;
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+        let choices = (0..n).map(|i| i % 5 == 0).collect::<Vec<_>>();
+
+        let receiver_choices = choices.clone();
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+
+            let mut receiver = IknpReceiver::setup(
+                &mut ot_sender,
+                ext_receiver_channel,
+                SecurityLevel::default(),
+                &mut rng,
+            )?;
+            receiver.receive_correlated(&receiver_choices)
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel).unwrap();
+
+        let mut sender = IknpSender::setup(
+            &mut ot_receiver,
+            ext_sender_channel,
+            SecurityLevel::default(),
+            &mut rng,
+        )?;
+        let keys = sender.send_correlated(n)?;
+        let delta = sender.delta();
+
+        let received = receiver_handle.join().unwrap()?;
+        for ((k, b), m) in keys.iter().zip(choices).zip(received) {
+            let expected = if b { *k ^ delta } else { *k };
+            assert_eq!(m, expected);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ot_extension_authenticated() -> Result<(), Box<dyn std::error::Error>> {
+        const M: usize = 300;
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+        let choices = (0..M).map(|i| i % 3 == 0).collect::<Vec<_>>();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+
+            let mut receiver = IknpReceiver::setup(
+                &mut ot_sender,
+                ext_receiver_channel,
+                SecurityLevel::default(),
+                &mut rng,
+            )?
+            .with_encryption(Encryption::Authenticated);
+
+            let blocks = receiver.receive::<Block128>(&choices)?;
+            let bytes = receiver.receive_bytes(&choices)?;
+            Ok::<_, OTError>((blocks, bytes))
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel)?;
+
+        let mut sender = IknpSender::setup(
+            &mut ot_receiver,
+            ext_sender_channel,
+            SecurityLevel::default(),
+            &mut rng,
+        )?
+        .with_encryption(Encryption::Authenticated);
+
+        let blocks = (0..M as u128)
+            .map(|i| [Block128::from(2 * i), Block128::from(2 * i + 1)])
+            .collect::<Vec<_>>();
+        let bytes = (0..M)
+            .map(|i| [vec![0u8; i % 40], vec![1u8; i % 7]])
+            .collect::<Vec<_>>();
+        sender.send(&blocks)?;
+        sender.send_bytes(&bytes)?;
+
+        let (received_blocks, received_bytes) = receiver_handle.join().unwrap()?;
+        for j in 0..M {
+            let b = (j % 3 == 0) as usize;
+            assert_eq!(received_blocks[j], blocks[j][b]);
+            assert_eq!(received_bytes[j], bytes[j][b]);
+        }
+
+        Ok(())
+    }
+
+    /// Run IKNP extension on top of the given pair of base OTs
+    fn run_with_base_ot<S, R>(
+        base_sender: impl FnOnce(MemChannel) -> OTResult<S> + Send + 'static,
+        base_receiver: impl FnOnce(MemChannel) -> OTResult<R>,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        S: OTSender,
+        R: OTReceiver,
+    {
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+        let choices = (0..M).map(|i| i % 3 == 1).collect::<Vec<_>>();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = base_sender(ot_sender_channel)?;
+            let mut receiver = IknpReceiver::setup(
+                &mut ot_sender,
+                ext_receiver_channel,
+                SecurityLevel::default(),
+                &mut rng,
+            )?;
+            receiver.receive::<Block128>(&choices)
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_receiver = base_receiver(ot_receiver_channel)?;
+        let mut sender = IknpSender::setup(
+            &mut ot_receiver,
+            ext_sender_channel,
+            SecurityLevel::default(),
+            &mut rng,
+        )?;
+        let values = (0..M as u128)
+            .map(|i| [Block128::from(2 * i), Block128::from(2 * i + 1)])
+            .collect::<Vec<_>>();
+        sender.send(&values)?;
+
+        let received = receiver_handle.join().unwrap()?;
+        for (j, v) in received.iter().enumerate() {
+            assert_eq!(*v, values[j][(j % 3 == 1) as usize]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_base_ots() -> Result<(), Box<dyn std::error::Error>> {
+        run_with_base_ot(
+            |channel| CO15Sender::setup(channel, &mut thread_rng()),
+            CO15Receiver::setup,
+        )?;
+        run_with_base_ot(
+            |channel| Ok(NP01Sender::new(channel)),
+            |channel| Ok(NP01Receiver::new(channel)),
+        )?;
+        run_with_base_ot(
+            |channel| MR19Sender::setup(channel, &mut thread_rng()),
+            MR19Receiver::setup,
+        )
+    }
 }
@@ -0,0 +1,326 @@
+//! Implements 1-out-of-N OT extension of Kolesnikov and Kumaresan with semi-honest security
+//! Refer: https://eprint.iacr.org/2013/491.pdf
+//!
+//! IKNP encodes the choice bit r_j with the repetition code, so that q_j = t_j ^ (r_j * s).
+//! KK13 replaces it with the Walsh-Hadamard code C of 8 bit inputs, which has length
+//! κ = 256 and distance 128. Then q_j = t_j ^ (C(r_j) & s), and the sender derives the key
+//! of x as H(j, q_j ^ (C(x) & s)), which equals H(j, t_j) only for x = r_j.
+//! N is chosen per call, up to `MAX_N`.
+use ark_std::rand::Rng;
+
+use crate::{
+    block::{Block, Block128},
+    channel::AbstractChannel,
+    ot::{OTError, OTReceiver, OTResult, OTSender},
+    prg::Prg,
+};
+
+use super::{bit_matrix::BitMatrix, iknp::hash_row};
+
+/// Number of base OTs, which is the length of the Walsh-Hadamard code
+const KAPPA: usize = 256;
+
+/// Maximum number of messages in one OT
+pub const MAX_N: usize = 256;
+
+/// Walsh-Hadamard codeword of x, where i-th bit is the parity of x & i
+fn codeword(x: usize) -> [u128; 2] {
+    let mut words = [0u128; 2];
+    for i in 0..KAPPA {
+        let bit = ((x & i).count_ones() & 1) as u128;
+        words[i / 128] |= bit << (i % 128);
+    }
+    words
+}
+
+fn check_n(n: usize) -> OTResult<()> {
+    if !(2..=MAX_N).contains(&n) {
+        return Err(OTError::InvalidParameter(format!(
+            "1-of-N OT requires 2 <= N <= {MAX_N}, got {n}"
+        )));
+    }
+    Ok(())
+}
+
+/// KK13 extension sender
+pub struct Kk13Sender<C: AbstractChannel> {
+    channel: C,
+    // 1 * κ matrix of base OT choices s
+    s: [u128; 2],
+    // PRG seeded with k_i^{s_i}
+    prgs: Vec<Prg>,
+    // codewords of all the inputs
+    codewords: Vec<[u128; 2]>,
+    counter: usize,
+}
+
+impl<C: AbstractChannel> Kk13Sender<C> {
+    /// Perform κ base OTs to receive seeds k_i^{s_i}.
+    /// Ext sender acts as an OT receiver
+    pub fn setup<OT: OTReceiver, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let s = [rng.gen::<u128>(), rng.gen::<u128>()];
+        let choices = (0..KAPPA)
+            .map(|i| ((s[i / 128] >> (i % 128)) & 1) as usize)
+            .collect::<Vec<_>>();
+        let prgs = base_ot
+            .receive_batch::<2, Block128, R>(&choices, rng)?
+            .into_iter()
+            .map(Prg::new)
+            .collect();
+
+        Ok(Self {
+            channel,
+            s,
+            prgs,
+            codewords: (0..MAX_N).map(codeword).collect(),
+            counter: 0,
+        })
+    }
+
+    /// Extend m random 1-of-n OTs. Returns n keys for each OT.
+    pub fn extend(&mut self, n: usize, m: usize) -> OTResult<Vec<Vec<[u8; 32]>>> {
+        check_n(n)?;
+
+        // q_i = PRG(k_i^{s_i}) ^ s_i * u_i
+        let mut q_matrix = BitMatrix::new(KAPPA, m);
+        let mut u_row = BitMatrix::new(1, m);
+        let mut u_bytes = vec![0u8; u_row.words_per_row() * 16];
+        for (i, prg) in self.prgs.iter_mut().enumerate() {
+            q_matrix
+                .row_mut(i)
+                .iter_mut()
+                .for_each(|w| *w = prg.gen_block());
+            self.channel.read_bytes(&mut u_bytes)?;
+            if (self.s[i / 128] >> (i % 128)) & 1 == 1 {
+                u_row.set_row_bytes(0, &u_bytes);
+                q_matrix.xor_row(i, u_row.row(0));
+            }
+        }
+        let q_matrix = q_matrix.transpose();
+
+        // k_j^x = H(j, q_j ^ (C(x) & s))
+        let keys = (0..m)
+            .map(|j| {
+                let q = q_matrix.row(j);
+                let q = [u128::from(q[0]), u128::from(q[1])];
+                self.codewords[..n]
+                    .iter()
+                    .map(|c| {
+                        let row = [0, 1].map(|w| Block128::from(q[w] ^ (c[w] & self.s[w])));
+                        hash_row(self.counter + j, &row)
+                    })
+                    .collect()
+            })
+            .collect();
+        self.counter += m;
+
+        Ok(keys)
+    }
+
+    /// Send n values for each OT, where n is the same for all the OTs
+    pub fn send<T: Block>(&mut self, values: &[Vec<T>]) -> OTResult<()> {
+        let n = values.first().map_or(2, |v| v.len());
+        if let Some(v) = values.iter().find(|v| v.len() != n) {
+            return Err(OTError::InvalidParameter(format!(
+                "all the OTs must have {n} values, got {}",
+                v.len()
+            )));
+        }
+
+        let keys = self.extend(n, values.len())?;
+        for (vs, ks) in values.iter().zip(keys) {
+            for (v, k) in vs.iter().zip(ks) {
+                self.channel.write_bytes(&v.encrypt(&k).as_bytes())?;
+            }
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+/// KK13 extension receiver
+pub struct Kk13Receiver<C: AbstractChannel> {
+    channel: C,
+    // PRGs seeded with (k_i^0, k_i^1)
+    prgs: Vec<[Prg; 2]>,
+    counter: usize,
+}
+
+impl<C: AbstractChannel> Kk13Receiver<C> {
+    /// Perform κ base OTs to send seed pairs (k_i^0, k_i^1).
+    /// Ext receiver acts as an OT sender
+    pub fn setup<OT: OTSender, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let seeds = (0..KAPPA)
+            .map(|_| {
+                [
+                    Block128::from(rng.gen::<u128>()),
+                    Block128::from(rng.gen::<u128>()),
+                ]
+            })
+            .collect::<Vec<_>>();
+        base_ot.send_batch(&seeds)?;
+        let prgs = seeds.into_iter().map(|seed| seed.map(Prg::new)).collect();
+
+        Ok(Self {
+            channel,
+            prgs,
+            counter: 0,
+        })
+    }
+
+    /// Extend random 1-of-n OTs for the choices. Returns the key of the choice for each OT.
+    pub fn extend(&mut self, n: usize, choices: &[usize]) -> OTResult<Vec<[u8; 32]>> {
+        check_n(n)?;
+        if let Some(&choice) = choices.iter().find(|&&c| c >= n) {
+            return Err(OTError::InvalidChoice(choice));
+        }
+        let m = choices.len();
+
+        // m * κ matrix whose j-th row is C(r_j)
+        let mut d_matrix = BitMatrix::new(m, KAPPA);
+        for (j, &choice) in choices.iter().enumerate() {
+            let c = codeword(choice);
+            d_matrix.row_mut(j).copy_from_slice(&c.map(Block128::from));
+        }
+        let d_matrix = d_matrix.transpose();
+
+        // t_i = PRG(k_i^0), u_i = t_i ^ PRG(k_i^1) ^ d_i
+        let mut t_matrix = BitMatrix::new(KAPPA, m);
+        let mut u_row = BitMatrix::new(1, m);
+        for (i, [prg_0, prg_1]) in self.prgs.iter_mut().enumerate() {
+            t_matrix
+                .row_mut(i)
+                .iter_mut()
+                .for_each(|w| *w = prg_0.gen_block());
+            u_row
+                .row_mut(0)
+                .iter_mut()
+                .for_each(|w| *w = prg_1.gen_block());
+            u_row.xor_row(0, t_matrix.row(i));
+            u_row.xor_row(0, d_matrix.row(i));
+            self.channel.write_bytes(&u_row.row_bytes(0))?;
+        }
+        self.channel.flush()?;
+        let t_matrix = t_matrix.transpose();
+
+        let keys = (0..m)
+            .map(|j| hash_row(self.counter + j, t_matrix.row(j)))
+            .collect();
+        self.counter += m;
+
+        Ok(keys)
+    }
+
+    /// Receive the chosen value of 1-of-n OT for each of the choices
+    pub fn receive<T: Block + Default>(&mut self, n: usize, choices: &[usize]) -> OTResult<Vec<T>> {
+        let keys = self.extend(n, choices)?;
+
+        let d = T::default();
+        let mut bytes = vec![0u8; d.bytes_len()];
+        choices
+            .iter()
+            .zip(keys)
+            .map(|(&choice, key)| {
+                let mut res = None;
+                for i in 0..n {
+                    self.channel.read_bytes(&mut bytes)?;
+                    if i == choice {
+                        res = Some(T::from_bytes(&bytes).decrypt(&key));
+                    }
+                }
+                res.ok_or(OTError::InvalidChoice(choice))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::thread_rng;
+
+    use super::*;
+    use crate::{
+        channel::MemChannel,
+        ot::co15::{CO15Receiver, CO15Sender},
+    };
+
+    #[test]
+    fn test_codeword_distance() {
+        for x in 0..MAX_N {
+            for y in 0..x {
+                let (cx, cy) = (codeword(x), codeword(y));
+                let distance = (cx[0] ^ cy[0]).count_ones() + (cx[1] ^ cy[1]).count_ones();
+                assert_eq!(distance, 128);
+            }
+        }
+    }
+
+    #[test]
+    fn test_kk13() -> Result<(), Box<dyn std::error::Error>> {
+        const M: usize = 300;
+        let sizes = [2, 5, 256];
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+
+            let mut receiver = Kk13Receiver::setup(&mut ot_sender, ext_receiver_channel, &mut rng)?;
+
+            sizes
+                .iter()
+                .map(|&n| {
+                    let choices = (0..M).map(|j| (7 * j) % n).collect::<Vec<_>>();
+                    receiver.receive::<Block128>(n, &choices)
+                })
+                .collect::<OTResult<Vec<_>>>()
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel)?;
+
+        let mut sender = Kk13Sender::setup(&mut ot_receiver, ext_sender_channel, &mut rng)?;
+
+        for n in sizes {
+            let values = (0..M)
+                .map(|j| {
+                    (0..n)
+                        .map(|x| Block128::from((j * n + x) as u128))
+                        .collect()
+                })
+                .collect::<Vec<Vec<_>>>();
+            sender.send(&values)?;
+        }
+
+        let received = receiver_handle.join().unwrap()?;
+        for (n, result) in sizes.iter().zip(received) {
+            for (j, v) in result.iter().enumerate() {
+                assert_eq!(*v, Block128::from((j * n + (7 * j) % n) as u128));
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_n() {
+        assert!(matches!(check_n(1), Err(OTError::InvalidParameter(_))));
+        assert!(matches!(
+            check_n(MAX_N + 1),
+            Err(OTError::InvalidParameter(_))
+        ));
+        assert!(check_n(MAX_N).is_ok());
+    }
+}
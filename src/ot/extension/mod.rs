@@ -1 +1,6 @@
+pub mod bit_matrix;
+pub mod ferret;
+mod ggm;
 pub mod iknp;
+pub mod kk13;
+pub mod softspoken;
@@ -0,0 +1,378 @@
+//! Implements SoftSpokenOT extension with semi-honest security
+//! Refer: https://eprint.iacr.org/2022/192.pdf
+//!
+//! κ = 128 bits of Δ are split into blocks of k bits. For each block, the parties run a GGM
+//! tree with k base OTs so that the ext receiver knows all 2^k leaves r_x while the ext sender
+//! knows all of them except x = Δ_b. Small field VOLE follows from
+//! Σ_x (x ^ Δ_b) * r_x = Σ_x x * r_x ^ Δ_b * Σ_x r_x,
+//! and the receiver sends only one correction per block instead of one per bit as in IKNP.
+//! Larger k reduces the communication by factor of k at the cost of 2^k / k times more PRG calls.
+use ark_std::rand::Rng;
+use rand::prelude::thread_rng;
+
+use crate::{
+    block::{Block, Block128},
+    channel::AbstractChannel,
+    ot::{COTReceiver, COTSender, OTError, OTReceiver, OTResult, OTSender},
+    prg::Prg,
+};
+
+use super::{bit_matrix::BitMatrix, ggm, iknp::hash_row};
+
+/// Computational security parameter. Bit length of Δ.
+const KAPPA: usize = 128;
+/// Maximum k. Each block expands 2^k leaves.
+pub const MAX_K: usize = 10;
+
+/// Split κ bits into blocks of k bits. Returns (offset, size) of each block.
+fn blocks(k: usize) -> OTResult<Vec<(usize, usize)>> {
+    if k == 0 || k > MAX_K {
+        return Err(OTError::InvalidParameter(format!(
+            "SoftSpokenOT k must be in 1..={MAX_K}, got {k}"
+        )));
+    }
+    Ok((0..KAPPA)
+        .step_by(k)
+        .map(|offset| (offset, k.min(KAPPA - offset)))
+        .collect())
+}
+
+/// Fill the row with the next n bits of the PRG
+fn fill_row(row: &mut BitMatrix, prg: &mut Prg) {
+    row.row_mut(0).iter_mut().for_each(|w| *w = prg.gen_block());
+}
+
+/// Stateful SoftSpokenOT extension sender.
+pub struct SoftSpokenSender<C: AbstractChannel> {
+    channel: C,
+    blocks: Vec<(usize, usize)>,
+    // 1 * κ matrix of Δ
+    delta: BitMatrix,
+    // PRGs seeded with the leaves of each block. Leaf x = Δ_b is unknown.
+    prgs: Vec<Vec<Option<Prg>>>,
+    // number of OTs extended so far, used as a tweak of the hash
+    counter: usize,
+}
+
+impl<C: AbstractChannel> SoftSpokenSender<C> {
+    /// Perform k base OTs for each block to receive all the leaves except Δ_b.
+    /// Ext sender acts as an OT receiver
+    pub fn setup<OT: OTReceiver, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        k: usize,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let blocks = blocks(k)?;
+        let delta = BitMatrix::random(1, KAPPA, rng);
+
+        // Choices of all the levels of all the blocks are sent at once
+        let alphas = blocks
+            .iter()
+            .map(|&(offset, size)| {
+                (0..size).fold(0, |acc, i| acc | (delta.get(0, offset + i) as usize) << i)
+            })
+            .collect::<Vec<_>>();
+        let choices = blocks
+            .iter()
+            .zip(&alphas)
+            .flat_map(|(&(_, size), &alpha)| {
+                (0..size).map(move |level| ggm::sibling_bit(alpha, size, level) as usize)
+            })
+            .collect::<Vec<_>>();
+        let mut sums = base_ot
+            .receive_batch::<2, Block128, R>(&choices, rng)?
+            .into_iter();
+
+        let prgs = blocks
+            .iter()
+            .zip(alphas)
+            .map(|(&(_, size), alpha)| {
+                let sums = sums.by_ref().take(size).collect::<Vec<_>>();
+                ggm::punctured_tree(alpha, size, &sums)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(x, leaf)| (x != alpha).then(|| Prg::new(leaf)))
+                    .collect()
+            })
+            .collect();
+
+        Ok(Self {
+            channel,
+            blocks,
+            delta,
+            prgs,
+            counter: 0,
+        })
+    }
+
+    /// Receive corrections and compute n * κ matrix where j-th row is q_j = t_j ^ (r_j * Δ)
+    fn extend_q(&mut self, n: usize) -> OTResult<BitMatrix> {
+        let mut q_matrix = BitMatrix::new(KAPPA, n);
+        let mut r_x = BitMatrix::new(1, n);
+        let mut c_row = BitMatrix::new(1, n);
+        let mut c_bytes = vec![0u8; c_row.words_per_row() * 16];
+
+        for (&(offset, size), prgs) in self.blocks.iter().zip(self.prgs.iter_mut()) {
+            // w_i = Σ_x (x_i ^ Δ_i) * r_x
+            for (x, prg) in prgs.iter_mut().enumerate() {
+                let Some(prg) = prg else { continue };
+                fill_row(&mut r_x, prg);
+                for i in 0..size {
+                    if ((x >> i) & 1 == 1) ^ self.delta.get(0, offset + i) {
+                        q_matrix.xor_row(offset + i, r_x.row(0));
+                    }
+                }
+            }
+
+            // q_i = w_i ^ Δ_i * (u ^ r) = v_i ^ Δ_i * r
+            self.channel.read_bytes(&mut c_bytes)?;
+            c_row.set_row_bytes(0, &c_bytes);
+            for i in 0..size {
+                if self.delta.get(0, offset + i) {
+                    q_matrix.xor_row(offset + i, c_row.row(0));
+                }
+            }
+        }
+
+        Ok(q_matrix.transpose())
+    }
+
+    /// Extend n random OTs. Returns pair of keys for each OT.
+    pub fn extend(&mut self, n: usize) -> OTResult<Vec<[[u8; 32]; 2]>> {
+        let q_matrix = self.extend_q(n)?;
+
+        let mut q_row_delta = BitMatrix::new(1, KAPPA);
+        let keys = (0..n)
+            .map(|j| {
+                let tweak = self.counter + j;
+                q_row_delta.row_mut(0).copy_from_slice(q_matrix.row(j));
+                q_row_delta.xor_row(0, self.delta.row(0));
+                [
+                    hash_row(tweak, q_matrix.row(j)),
+                    hash_row(tweak, q_row_delta.row(0)),
+                ]
+            })
+            .collect();
+        self.counter += n;
+
+        Ok(keys)
+    }
+
+    /// Send a pair of values for each OT
+    pub fn send<T: Block>(&mut self, values: &[[T; 2]]) -> OTResult<()> {
+        let keys = self.extend(values.len())?;
+        for (v, k) in values.iter().zip(keys) {
+            self.channel.write_bytes(&v[0].encrypt(&k[0]).as_bytes())?;
+            self.channel.write_bytes(&v[1].encrypt(&k[1]).as_bytes())?;
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Stateful SoftSpokenOT extension receiver.
+pub struct SoftSpokenReceiver<C: AbstractChannel> {
+    channel: C,
+    blocks: Vec<(usize, usize)>,
+    // PRGs seeded with all the leaves of each block
+    prgs: Vec<Vec<Prg>>,
+    counter: usize,
+}
+
+impl<C: AbstractChannel> SoftSpokenReceiver<C> {
+    /// Expand GGM tree for each block and send the level sums with k base OTs.
+    /// Ext receiver acts as an OT sender
+    pub fn setup<OT: OTSender, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        k: usize,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let blocks = blocks(k)?;
+
+        let mut prgs = Vec::with_capacity(blocks.len());
+        let mut sums = Vec::with_capacity(KAPPA);
+        for &(_, size) in blocks.iter() {
+            let (leaves, tree_sums) = ggm::tree(Block128::from(rng.gen::<u128>()), size);
+            sums.extend(tree_sums);
+            prgs.push(leaves.into_iter().map(Prg::new).collect());
+        }
+        base_ot.send_batch(&sums)?;
+
+        Ok(Self {
+            channel,
+            blocks,
+            prgs,
+            counter: 0,
+        })
+    }
+
+    /// Send corrections for the choices and return n * κ matrix where j-th row is t_j
+    fn extend_t(&mut self, choices: &[bool]) -> OTResult<BitMatrix> {
+        let n = choices.len();
+        let mut r_row = BitMatrix::new(1, n);
+        for (j, &b) in choices.iter().enumerate() {
+            r_row.set(0, j, b);
+        }
+
+        let mut t_matrix = BitMatrix::new(KAPPA, n);
+        let mut r_x = BitMatrix::new(1, n);
+        for (&(offset, size), prgs) in self.blocks.iter().zip(self.prgs.iter_mut()) {
+            // u = Σ_x r_x, v_i = Σ_x x_i * r_x
+            let mut u_row = r_row.clone();
+            for (x, prg) in prgs.iter_mut().enumerate() {
+                fill_row(&mut r_x, prg);
+                u_row.xor_row(0, r_x.row(0));
+                for i in (0..size).filter(|i| (x >> i) & 1 == 1) {
+                    t_matrix.xor_row(offset + i, r_x.row(0));
+                }
+            }
+            // send correction u ^ r
+            self.channel.write_bytes(&u_row.row_bytes(0))?;
+        }
+        self.channel.flush()?;
+
+        Ok(t_matrix.transpose())
+    }
+
+    /// Extend OTs for the given choices. Returns the key of the choice for each OT.
+    pub fn extend(&mut self, choices: &[bool]) -> OTResult<Vec<[u8; 32]>> {
+        let n = choices.len();
+        let t_matrix = self.extend_t(choices)?;
+
+        let keys = (0..n)
+            .map(|j| hash_row(self.counter + j, t_matrix.row(j)))
+            .collect();
+        self.counter += n;
+
+        Ok(keys)
+    }
+
+    /// Receive the chosen value for each of the choices
+    pub fn receive<T: Block + Default>(&mut self, choices: &[bool]) -> OTResult<Vec<T>> {
+        let keys = self.extend(choices)?;
+
+        let d = T::default();
+        let mut v_bytes = [vec![0u8; d.bytes_len()], vec![0u8; d.bytes_len()]];
+        choices
+            .iter()
+            .zip(keys)
+            .map(|(&b, key)| {
+                self.channel.read_bytes(&mut v_bytes[0])?;
+                self.channel.read_bytes(&mut v_bytes[1])?;
+                Ok(T::from_bytes(&v_bytes[b as usize]).decrypt(&key))
+            })
+            .collect()
+    }
+}
+
+impl<C: AbstractChannel> COTSender for SoftSpokenSender<C> {
+    fn delta(&self) -> Block128 {
+        self.delta.row(0)[0]
+    }
+
+    fn send_correlated(&mut self, n: usize) -> OTResult<Vec<Block128>> {
+        let q_matrix = self.extend_q(n)?;
+        self.counter += n;
+        Ok((0..n).map(|j| q_matrix.row(j)[0]).collect())
+    }
+}
+
+impl<C: AbstractChannel> COTReceiver for SoftSpokenReceiver<C> {
+    fn receive_correlated(&mut self, choices: &[bool]) -> OTResult<Vec<Block128>> {
+        let t_matrix = self.extend_t(choices)?;
+        self.counter += choices.len();
+        Ok((0..choices.len()).map(|j| t_matrix.row(j)[0]).collect())
+    }
+}
+
+/// SoftSpokenOT extension sender for the number of OTs chosen at runtime.
+pub fn ot_ext_send_vec<Receiver: OTReceiver, T: Block, C: AbstractChannel>(
+    receiver: &mut Receiver,
+    values: &[[T; 2]],
+    channel: &mut C,
+    k: usize,
+) -> OTResult<()> {
+    let mut sender = SoftSpokenSender::setup(receiver, channel.clone(), k, &mut thread_rng())?;
+    sender.send(values)
+}
+
+/// SoftSpokenOT extension receiver for the number of OTs chosen at runtime.
+/// Returns the chosen value for each of the choices.
+pub fn ot_ext_receive_vec<Sender: OTSender, T: Block + Default, C: AbstractChannel>(
+    sender: &mut Sender,
+    choices: &[bool],
+    channel: &mut C,
+    k: usize,
+) -> OTResult<Vec<T>> {
+    let mut receiver = SoftSpokenReceiver::setup(sender, channel.clone(), k, &mut thread_rng())?;
+    receiver.receive(choices)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::thread_rng;
+
+    use super::*;
+    use crate::{
+        channel::MemChannel,
+        ot::co15::{CO15Receiver, CO15Sender},
+    };
+
+    fn run_ot_extension(k: usize, m: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (mut ext_sender_channel, mut ext_receiver_channel) = MemChannel::pair();
+        let choices = (0..m).map(|i| i % 3 == 0).collect::<Vec<_>>();
+        let values = (0..m as u128)
+            .map(|i| [Block128::from(i), Block128::from(i + m as u128)])
+            .collect::<Vec<_>>();
+
+        let receiver_choices = choices.clone();
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+
+            ot_ext_receive_vec::<_, Block128, _>(
+                &mut ot_sender,
+                &receiver_choices,
+                &mut ext_receiver_channel,
+                k,
+            )
+        });
+
+        let mut ot_receiver = CO15Receiver::setup(ot_receiver_channel).unwrap();
+
+        ot_ext_send_vec(&mut ot_receiver, &values, &mut ext_sender_channel, k)?;
+
+        let receiver_result = receiver_handle.join().unwrap()?;
+        assert_eq!(receiver_result.len(), m);
+        for ((v, c), r) in values.iter().zip(choices).zip(receiver_result) {
+            assert_eq!(v[c as usize], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_softspoken() -> Result<(), Box<dyn std::error::Error>> {
+        // k = 3 does not divide κ
+        for k in [1, 3, 8] {
+            run_ot_extension(k, 1000)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_k() {
+        assert!(matches!(blocks(0), Err(OTError::InvalidParameter(_))));
+        assert!(matches!(
+            blocks(MAX_K + 1),
+            Err(OTError::InvalidParameter(_))
+        ));
+        assert_eq!(blocks(5).unwrap().last(), Some(&(125, 3)));
+    }
+}
@@ -1,18 +1,453 @@
 //! This module implements oblivious trasnfer implementation described in
 //! https://eprint.iacr.org/2015/546.pdf
+//!
+//! Extension sender acts as a receiver of K base OTs and holds global Δ = (s_1, ..., s_K).
+//! Extension receiver acts as a sender of K base OTs and sends the seeds (k_i^0, k_i^1).
+//! Random OTs are produced in batches, and the correlation is checked with random linear
+//! combination over GF(2^128) before any of the outputs is used.
+use ark_std::rand::Rng;
+use rand::prelude::thread_rng;
+use sha3::{Digest, Keccak256};
 
-use crate::AbstractChannel;
+use crate::{
+    block::{Block, Block128},
+    channel::AbstractChannel,
+    prg::Prg,
+};
+
+use super::extension::bit_matrix::BitMatrix;
+use super::{check_arity, OTError, OTReceiver, OTResult, OTSender, ROTReceiver, ROTSender};
+
+/// Computational security parameter. Number of base OTs.
+const K: usize = 128;
+/// Statistical security parameter
+const S: usize = 64;
+/// Number of random OTs extended at once when the pool runs out
+const BATCH: usize = 1024;
+
+/// Derive key from j-th row of the matrix
+fn hash_row(j: u64, row: Block128) -> [u8; 32] {
+    let mut hasher = Keccak256::default();
+    hasher.update(j.to_le_bytes());
+    hasher.update(row.as_bytes());
+    hasher.finalize().into()
+}
 
 pub struct Kos15Sender<C: AbstractChannel> {
     channel: C,
+    delta: u128,
+    // PRG seeded with k_i^{s_i}
+    prgs: Vec<Prg>,
+    // number of random OTs generated so far, used as a tweak of the hash
+    counter: u64,
+    pool: Vec<[[u8; 32]; 2]>,
+    pos: usize,
+}
+
+impl<C: AbstractChannel> Kos15Sender<C> {
+    /// Perform K base OTs using the given OT receiver
+    pub fn setup<OT: OTReceiver, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let delta: u128 = rng.gen();
+        let choices = (0..K)
+            .map(|i| ((delta >> i) & 1) as usize)
+            .collect::<Vec<_>>();
+        let prgs = base_ot
+            .receive_batch::<2, Block128, R>(&choices, rng)?
+            .into_iter()
+            .map(Prg::new)
+            .collect();
+
+        Ok(Self {
+            channel,
+            delta,
+            prgs,
+            counter: 0,
+            pool: vec![],
+            pos: 0,
+        })
+    }
+
+    /// Extend m random OTs. Returns pair of keys for each OT.
+    pub fn extend(&mut self, m: usize) -> OTResult<Vec<[[u8; 32]; 2]>> {
+        let m_ext = m + K + S;
+
+        // q_i = PRG(k_i^{s_i}) ^ s_i * u_i
+        let mut q_matrix = BitMatrix::new(K, m_ext);
+        let mut u_bytes = vec![0u8; q_matrix.words_per_row() * 16];
+        for (i, prg) in self.prgs.iter_mut().enumerate() {
+            q_matrix
+                .row_mut(i)
+                .iter_mut()
+                .for_each(|w| *w = prg.gen_block());
+            self.channel.read_bytes(&mut u_bytes)?;
+            if (self.delta >> i) & 1 == 1 {
+                let mut u_row = BitMatrix::new(1, m_ext);
+                u_row.set_row_bytes(0, &u_bytes);
+                q_matrix.xor_row(i, u_row.row(0));
+            }
+        }
+        let q_matrix = q_matrix.transpose();
+        let q_rows = (0..m_ext).map(|j| q_matrix.row(j)[0]).collect::<Vec<_>>();
+
+        // Correlation check
+        let seed: u128 = thread_rng().gen();
+        self.channel.write_bytes(&seed.to_le_bytes())?;
+        self.channel.flush()?;
+
+        let mut chi_prg = Prg::new(Block128::from(seed));
+        let q = q_rows.iter().fold(Block128::default(), |acc, q_j| {
+            let chi = chi_prg.gen_block();
+            acc ^ chi.gf_mul(q_j)
+        });
+
+        let mut x_bytes = [0u8; 16];
+        let mut t_bytes = [0u8; 16];
+        self.channel.read_bytes(&mut x_bytes)?;
+        self.channel.read_bytes(&mut t_bytes)?;
+        let x = Block128::from(x_bytes);
+        let t = Block128::from(t_bytes);
+
+        if q != t ^ x.gf_mul(&Block128::from(self.delta)) {
+            return Err(OTError::ConsistencyCheckFailed);
+        }
+
+        // Discard K + S rows used for the check
+        let delta = Block128::from(self.delta);
+        let keys = q_rows
+            .iter()
+            .take(m)
+            .map(|q_j| {
+                let j = self.counter;
+                self.counter += 1;
+                [hash_row(j, *q_j), hash_row(j, *q_j ^ delta)]
+            })
+            .collect();
+
+        Ok(keys)
+    }
+
+    fn next_keys(&mut self) -> OTResult<[[u8; 32]; 2]> {
+        if self.pos == self.pool.len() {
+            self.pool = self.extend(BATCH)?;
+            self.pos = 0;
+        }
+        let keys = self.pool[self.pos];
+        self.pos += 1;
+        Ok(keys)
+    }
+
+    /// Receive choice correction d from receiver and return keys in the order of the choice
+    fn derandomize(&mut self) -> OTResult<[[u8; 32]; 2]> {
+        let keys = self.next_keys()?;
+        let mut d = [0u8; 1];
+        self.channel.read_bytes(&mut d)?;
+        let d = match d[0] {
+            0 | 1 => d[0] as usize,
+            b => return Err(OTError::MalformedMessage(format!("choice correction {b}"))),
+        };
+        Ok([keys[d], keys[1 ^ d]])
+    }
+}
+
+impl<C: AbstractChannel> OTSender for Kos15Sender<C> {
+    fn send<const N: usize, T>(&mut self, values: [T; N]) -> OTResult<()>
+    where
+        T: Block + Clone,
+    {
+        check_arity::<N>()?;
+        let keys = self.derandomize()?;
+        for (v, key) in values.iter().zip(keys) {
+            self.channel.write_bytes(&v.encrypt(&key).as_bytes())?;
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<C: AbstractChannel> ROTSender for Kos15Sender<C> {
+    fn send_random<const N: usize, T: Block>(&mut self) -> OTResult<[T; N]> {
+        check_arity::<N>()?;
+        let keys = self.derandomize()?;
+        Ok(std::array::from_fn(|i| T::from_bytes(&keys[i])))
+    }
 }
 
 pub struct Kos15Receiver<C: AbstractChannel> {
     channel: C,
+    // PRGs seeded with (k_i^0, k_i^1)
+    prgs: Vec<[Prg; 2]>,
+    counter: u64,
+    pool: Vec<(bool, [u8; 32])>,
+    pos: usize,
+}
+
+impl<C: AbstractChannel> Kos15Receiver<C> {
+    /// Perform K base OTs using the given OT sender
+    pub fn setup<OT: OTSender, R: Rng>(
+        base_ot: &mut OT,
+        channel: C,
+        rng: &mut R,
+    ) -> OTResult<Self> {
+        let seeds = (0..K)
+            .map(|_| {
+                [
+                    Block128::from(rng.gen::<u128>()),
+                    Block128::from(rng.gen::<u128>()),
+                ]
+            })
+            .collect::<Vec<_>>();
+        base_ot.send_batch(&seeds)?;
+        let prgs = seeds.into_iter().map(|seed| seed.map(Prg::new)).collect();
+
+        Ok(Self {
+            channel,
+            prgs,
+            counter: 0,
+            pool: vec![],
+            pos: 0,
+        })
+    }
+
+    /// Extend m random OTs. Returns random choice bit and the key of the choice for each OT.
+    pub fn extend<R: Rng>(&mut self, m: usize, rng: &mut R) -> OTResult<Vec<(bool, [u8; 32])>> {
+        let m_ext = m + K + S;
+        let r_row = BitMatrix::random(1, m_ext, rng);
+
+        // u_i = t_i^0 ^ t_i^1 ^ r
+        let mut t_matrix = BitMatrix::new(K, m_ext);
+        let mut u_row = BitMatrix::new(1, m_ext);
+        for (i, [prg_0, prg_1]) in self.prgs.iter_mut().enumerate() {
+            t_matrix
+                .row_mut(i)
+                .iter_mut()
+                .for_each(|w| *w = prg_0.gen_block());
+            u_row
+                .row_mut(0)
+                .iter_mut()
+                .for_each(|w| *w = prg_1.gen_block());
+            u_row.xor_row(0, t_matrix.row(i));
+            u_row.xor_row(0, r_row.row(0));
+            self.channel.write_bytes(&u_row.row_bytes(0))?;
+        }
+        self.channel.flush()?;
+        let t_matrix = t_matrix.transpose();
+        let t_rows = (0..m_ext).map(|j| t_matrix.row(j)[0]).collect::<Vec<_>>();
+        let choices = (0..m_ext).map(|j| r_row.get(0, j)).collect::<Vec<_>>();
+
+        // Correlation check
+        let mut seed = [0u8; 16];
+        self.channel.read_bytes(&mut seed)?;
+        let mut chi_prg = Prg::new(Block128::from(u128::from_le_bytes(seed)));
+
+        let mut x = Block128::default();
+        let mut t = Block128::default();
+        for (t_j, r_j) in t_rows.iter().zip(&choices) {
+            let chi = chi_prg.gen_block();
+            if *r_j {
+                x = x ^ chi;
+            }
+            t = t ^ chi.gf_mul(t_j);
+        }
+        self.channel.write_bytes(&x.as_bytes())?;
+        self.channel.write_bytes(&t.as_bytes())?;
+        self.channel.flush()?;
+
+        let keys = t_rows
+            .iter()
+            .zip(choices)
+            .take(m)
+            .map(|(t_j, r_j)| {
+                let j = self.counter;
+                self.counter += 1;
+                (r_j, hash_row(j, *t_j))
+            })
+            .collect();
+
+        Ok(keys)
+    }
+
+    fn next_key<R: Rng>(&mut self, rng: &mut R) -> OTResult<(bool, [u8; 32])> {
+        if self.pos == self.pool.len() {
+            self.pool = self.extend(BATCH, rng)?;
+            self.pos = 0;
+        }
+        let key = self.pool[self.pos];
+        self.pos += 1;
+        Ok(key)
+    }
+
+    /// Send choice correction d = choice ^ r to sender and return the key of the choice
+    fn derandomize<R: Rng>(&mut self, choice: usize, rng: &mut R) -> OTResult<[u8; 32]> {
+        if choice > 1 {
+            return Err(OTError::InvalidChoice(choice));
+        }
+        let (r, key) = self.next_key(rng)?;
+        let d = (choice == 1) ^ r;
+        self.channel.write_bytes(&[d as u8])?;
+        self.channel.flush()?;
+        Ok(key)
+    }
+}
+
+impl<C: AbstractChannel> OTReceiver for Kos15Receiver<C> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        check_arity::<N>()?;
+        let key = self.derandomize(choice, rng)?;
+
+        let d = T::default();
+        let mut ciphertexts = [vec![0u8; d.bytes_len()], vec![0u8; d.bytes_len()]];
+        for c in ciphertexts.iter_mut() {
+            self.channel.read_bytes(c)?;
+        }
+
+        Ok(T::from_bytes(&ciphertexts[choice]).decrypt(&key))
+    }
+}
+
+impl<C: AbstractChannel> ROTReceiver for Kos15Receiver<C> {
+    fn receive_random<const N: usize, T: Block, R: Rng>(
+        &mut self,
+        choice: usize,
+        rng: &mut R,
+    ) -> OTResult<T> {
+        check_arity::<N>()?;
+        let key = self.derandomize(choice, rng)?;
+        Ok(T::from_bytes(&key))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::thread;
+
+    use rand::{
+        prelude::{thread_rng, ThreadRng},
+        RngCore,
+    };
+
+    use super::*;
+    use crate::{
+        channel::MemChannel,
+        ot::co15::{CO15Receiver, CO15Sender},
+    };
+
+    /// Run KOS15 setup on top of CO15 base OTs, then run f_sender and f_receiver on each side
+    fn run<S, R, A, B>(f_sender: S, f_receiver: R) -> (A, B)
+    where
+        S: FnOnce(Kos15Sender<MemChannel>) -> A,
+        R: FnOnce(Kos15Receiver<MemChannel>) -> B + Send + 'static,
+        B: Send + 'static,
+    {
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut base_ot = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+            let receiver =
+                Kos15Receiver::setup(&mut base_ot, ext_receiver_channel, &mut rng).unwrap();
+            f_receiver(receiver)
+        });
+
+        let mut rng = thread_rng();
+        let mut base_ot = CO15Receiver::setup(ot_receiver_channel).unwrap();
+        let sender = Kos15Sender::setup(&mut base_ot, ext_sender_channel, &mut rng).unwrap();
+        let a = f_sender(sender);
+
+        (a, receiver_handle.join().unwrap())
+    }
+
     #[test]
-    fn test_ot() {}
+    fn test_ot() -> Result<(), Box<dyn std::error::Error>> {
+        let choices = [true, false, false, true, true];
+
+        let (sent, received) = run(
+            |mut sender| {
+                (0..choices.len()).try_for_each(|i| {
+                    let values = [Block128::from(i as u128), Block128::from(100 + i as u128)];
+                    sender.send(values)
+                })
+            },
+            move |mut receiver| {
+                let mut rng = thread_rng();
+                choices
+                    .iter()
+                    .map(|&c| receiver.receive::<2, Block128, ThreadRng>(c as usize, &mut rng))
+                    .collect::<OTResult<Vec<_>>>()
+            },
+        );
+        sent?;
+
+        for (i, (v, c)) in received?.iter().zip(choices).enumerate() {
+            let expected = if c { 100 + i as u128 } else { i as u128 };
+            assert_eq!(*v, Block128::from(expected));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_ot() -> Result<(), Box<dyn std::error::Error>> {
+        const M: usize = 2000;
+
+        let (keys, received) = run(
+            |mut sender| sender.extend(M),
+            |mut receiver| receiver.extend(M, &mut thread_rng()),
+        );
+        let keys = keys?;
+
+        assert_eq!(keys.len(), M);
+        for (k, (r, key)) in keys.iter().zip(received?) {
+            assert_eq!(k[r as usize], key);
+            assert_ne!(k[1 - r as usize], key);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_consistency_check_failure() {
+        const M: usize = 100;
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, mut ext_receiver_channel) = MemChannel::pair();
+
+        // Malicious receiver sends random u columns and check values
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut base_ot = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
+            for _ in 0..K {
+                let seeds = [Block128::from(rng.gen::<u128>()), Block128::from(0)];
+                base_ot.send(seeds).unwrap();
+            }
+
+            let mut u = vec![0u8; (M + K + S).div_ceil(128) * 16 * K];
+            rng.fill_bytes(&mut u);
+            ext_receiver_channel.write_bytes(&u).unwrap();
+            ext_receiver_channel.flush().unwrap();
+
+            let mut seed = [0u8; 16];
+            ext_receiver_channel.read_bytes(&mut seed).unwrap();
+            let mut check = [0u8; 32];
+            rng.fill_bytes(&mut check);
+            ext_receiver_channel.write_bytes(&check).unwrap();
+            ext_receiver_channel.flush().unwrap();
+        });
+
+        let mut rng = thread_rng();
+        let mut base_ot = CO15Receiver::setup(ot_receiver_channel).unwrap();
+        let mut sender = Kos15Sender::setup(&mut base_ot, ext_sender_channel, &mut rng).unwrap();
+        let result = sender.extend(M);
+        receiver_handle.join().unwrap();
+
+        assert!(matches!(result, Err(OTError::ConsistencyCheckFailed)));
+    }
 }
@@ -1,12 +1,25 @@
 //! Implement 1-of-n oblivious transfer trait
+use std::io::ErrorKind;
+
 use ark_serialize::SerializationError;
 use ark_std::rand::Rng;
 
-use crate::{block::Block, channel::ChannelError};
+use crate::{
+    block::{Block, Block128, Block256},
+    channel::ChannelError,
+    store::StoreError,
+};
 
+pub mod auth;
+pub mod beaver;
 pub mod co15;
+pub mod cot;
+pub mod encoding;
 pub mod extension;
 pub mod kos15;
+pub mod mr19;
+pub mod np01;
+pub mod one_of_n;
 
 #[derive(thiserror::Error, Debug)]
 pub enum OTError {
@@ -16,18 +29,93 @@ pub enum OTError {
         source: SerializationError,
     },
     #[error(transparent)]
-    Channel {
-        #[from]
-        source: ChannelError,
-    },
+    Channel { source: ChannelError },
+    #[error("only 1-of-2 OT is supported, got 1-of-{0}")]
+    InvalidArity(usize),
+    #[error("choice {0} is out of range")]
+    InvalidChoice(usize),
+    #[error("invalid parameter: {0}")]
+    InvalidParameter(String),
+    #[error("correlated OT requires κ = 128, got {0}")]
+    UnsupportedSecurityLevel(usize),
+    #[error("correlation consistency check failed")]
+    ConsistencyCheckFailed,
+    #[error("invalid group element: {0}")]
+    InvalidPoint(&'static str),
+    #[error("malformed message: {0}")]
+    MalformedMessage(String),
+    #[error("failed to decrypt OT message")]
+    DecryptionFailed,
+    #[error("peer aborted the protocol")]
+    PeerAbort,
+    #[error(transparent)]
+    Store { source: Box<StoreError> },
+}
+
+impl From<StoreError> for OTError {
+    fn from(source: StoreError) -> Self {
+        OTError::Store {
+            source: Box::new(source),
+        }
+    }
+}
+
+/// Classify channel errors by their cause.
+/// A closed connection means the peer aborted, and undecodable data means the peer sent
+/// a malformed message. Any other failure is reported as a channel error.
+impl From<ChannelError> for OTError {
+    fn from(source: ChannelError) -> Self {
+        match source {
+            ChannelError::Io { source }
+                if matches!(
+                    source.kind(),
+                    ErrorKind::UnexpectedEof
+                        | ErrorKind::BrokenPipe
+                        | ErrorKind::ConnectionReset
+                        | ErrorKind::ConnectionAborted
+                ) =>
+            {
+                OTError::PeerAbort
+            }
+            ChannelError::Serialize { source } => OTError::MalformedMessage(source.to_string()),
+            ChannelError::InvalidFrame(message) => OTError::MalformedMessage(message),
+            source => OTError::Channel { source },
+        }
+    }
 }
 
 type OTResult<T> = Result<T, OTError>;
 
+/// Check that the OT is 1-of-2
+pub(crate) fn check_arity<const N: usize>() -> OTResult<()> {
+    if N != 2 {
+        return Err(OTError::InvalidArity(N));
+    }
+    Ok(())
+}
+
+/// Use a random OT output as a key
+pub(crate) fn rot_key(block: Block256) -> [u8; 32] {
+    let bytes = block.as_bytes();
+    std::array::from_fn(|i| bytes[i])
+}
+
 pub trait OTSender {
     fn send<const N: usize, T>(&mut self, values: [T; N]) -> OTResult<()>
     where
         T: Block + Clone;
+
+    /// Send values of many OTs.
+    /// Implementations can override this to run all the OTs in a single round.
+    fn send_batch<const N: usize, T>(&mut self, values: &[[T; N]]) -> OTResult<()>
+    where
+        T: Block + Clone,
+    {
+        for v in values {
+            self.send(v.clone())?;
+        }
+        Ok(())
+    }
 }
 
 pub trait OTReceiver {
@@ -35,6 +123,23 @@ pub trait OTReceiver {
     where
         T: Block + Clone + Default,
         R: Rng;
+
+    /// Receive values of many OTs.
+    /// Implementations can override this to run all the OTs in a single round.
+    fn receive_batch<const N: usize, T, R>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<T>>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        choices
+            .iter()
+            .map(|&c| self.receive::<N, T, R>(c, rng))
+            .collect()
+    }
 }
 
 /// Random OT sender
@@ -50,3 +155,18 @@ pub trait ROTReceiver {
         rng: &mut R,
     ) -> OTResult<T>;
 }
+
+/// Correlated OT sender.
+/// Sender holds global Δ and receives k_i for each OT.
+pub trait COTSender {
+    /// Global correlation Δ shared with all the OTs
+    fn delta(&self) -> Block128;
+
+    fn send_correlated(&mut self, n: usize) -> OTResult<Vec<Block128>>;
+}
+
+/// Correlated OT receiver.
+/// Receiver inputs b_i and receives k_i ^ b_i * Δ for each OT.
+pub trait COTReceiver {
+    fn receive_correlated(&mut self, choices: &[bool]) -> OTResult<Vec<Block128>>;
+}
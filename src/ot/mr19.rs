@@ -0,0 +1,297 @@
+//! This module implements endemic 1-of-2 oblivious transfer of Masny and Rindal described in
+//! https://eprint.iacr.org/2019/706.pdf, which is UC secure in the random oracle model.
+//!
+//! The sender samples a and sends A = aB once in setup.
+//! The receiver with choice c samples sk and random point r_{1-c}, sets
+//! r_c = skB - H(r_{1-c}) and sends (r_0, r_1). Both parties derive the public keys
+//! m_0 = r_0 + H(r_1) and m_1 = r_1 + H(r_0), so that m_c = skB.
+//! The sender encrypts v_i with H(j, i, a * m_i), and the receiver decrypts v_c with
+//! H(j, c, sk * A).
+use std::marker::PhantomData;
+
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::{rand::Rng, UniformRand};
+use sha3::{Digest, Keccak256};
+
+use crate::{block::*, channel::AbstractChannel, types::*};
+
+use super::{
+    auth::Encryption,
+    check_arity,
+    encoding::{GroupEncoding, Ristretto},
+    OTError, OTReceiver, OTResult, OTSender,
+};
+
+/// Maximum number of tries to hash into the group. Each try succeeds with probability about 1/2.
+const HASH_TO_GROUP_TRIES: u64 = 256;
+
+/// Hash a point into the prime order group with try-and-increment.
+/// Nobody knows the discrete log of the output.
+fn hash_to_group<P: CurveGroup, E: GroupEncoding<P>>(p: &P) -> OTResult<P> {
+    let encoded = E::encode(p)?;
+    for counter in 0..HASH_TO_GROUP_TRIES {
+        let bytes = [0u8, 1]
+            .iter()
+            .flat_map(|i| {
+                let mut hasher = Keccak256::default();
+                hasher.update(b"mr19 hash to group");
+                hasher.update(counter.to_le_bytes());
+                hasher.update([*i]);
+                hasher.update(&encoded);
+                hasher.finalize()
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(point) = P::Affine::from_random_bytes(&bytes) {
+            let point = point.clear_cofactor();
+            if !point.is_zero() {
+                return Ok(point.into_group());
+            }
+        }
+    }
+    Err(OTError::InvalidParameter(
+        "failed to hash into the group".to_string(),
+    ))
+}
+
+/// Key of i-th message of j-th OT
+fn key<P: CurveGroup, E: GroupEncoding<P>>(j: usize, i: usize, p: &P) -> OTResult<[u8; 32]> {
+    let mut hasher = Keccak256::default();
+    hasher.update((j as u64).to_le_bytes());
+    hasher.update((i as u64).to_le_bytes());
+    hasher.update(E::encode(p)?);
+    Ok(hasher.finalize().into())
+}
+
+/// Masny-Rindal sender over the group P, whose points are sent in encoding E
+pub struct MR19Sender<C: AbstractChannel, P: CurveGroup = G, E: GroupEncoding<P> = Ristretto> {
+    pub channel: C,
+
+    a: P::ScalarField,
+    // number of OTs performed so far, used as a tweak of the key
+    counter: usize,
+    encryption: Encryption,
+    _encoding: PhantomData<E>,
+}
+
+impl<C: AbstractChannel> MR19Sender<C> {
+    /// Setup the sender over ed25519
+    pub fn setup<R: Rng>(channel: C, rng: &mut R) -> OTResult<Self> {
+        Self::setup_with_group(channel, rng)
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> MR19Sender<C, P, E> {
+    /// Setup the sender over the group P
+    pub fn setup_with_group<R: Rng>(mut channel: C, rng: &mut R) -> OTResult<Self> {
+        // Sample a and send A = aB to receiver
+        let a = P::ScalarField::rand(rng);
+        E::write(&mut channel, &(P::generator() * a))?;
+        channel.flush()?;
+
+        Ok(Self {
+            channel,
+            a,
+            counter: 0,
+            encryption: Encryption::default(),
+            _encoding: PhantomData,
+        })
+    }
+
+    /// Set the encryption of the messages. The receiver has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> OTSender for MR19Sender<C, P, E> {
+    fn send<const N: usize, T: Block + Clone>(&mut self, values: [T; N]) -> OTResult<()> {
+        self.send_batch(&[values])
+    }
+
+    /// Receive (r_0, r_1) of all the OTs in one message, and reply all the ciphertexts at once.
+    fn send_batch<const N: usize, T: Block + Clone>(&mut self, values: &[[T; N]]) -> OTResult<()> {
+        check_arity::<N>()?;
+
+        let rs = values
+            .iter()
+            .map(|_| Ok([E::read(&mut self.channel)?, E::read(&mut self.channel)?]))
+            .collect::<OTResult<Vec<_>>>()?;
+
+        for (j, ([r0, r1], vs)) in rs.iter().zip(values).enumerate() {
+            // m_0 = r_0 + H(r_1), m_1 = r_1 + H(r_0)
+            let ms = [
+                *r0 + hash_to_group::<P, E>(r1)?,
+                *r1 + hash_to_group::<P, E>(r0)?,
+            ];
+            for (i, (v, m)) in vs.iter().zip(ms).enumerate() {
+                let k = key::<P, E>(self.counter + j, i, &(m * self.a))?;
+                self.channel.write_bytes(&self.encryption.encrypt(v, &k))?;
+            }
+        }
+        self.channel.flush()?;
+        self.counter += values.len();
+
+        Ok(())
+    }
+}
+
+/// Masny-Rindal receiver over the group P, whose points are sent in encoding E
+pub struct MR19Receiver<C: AbstractChannel, P: CurveGroup = G, E: GroupEncoding<P> = Ristretto> {
+    pub channel: C,
+
+    a: P,
+    counter: usize,
+    encryption: Encryption,
+    _encoding: PhantomData<E>,
+}
+
+impl<C: AbstractChannel> MR19Receiver<C> {
+    /// Setup the receiver over ed25519
+    pub fn setup(channel: C) -> OTResult<Self> {
+        Self::setup_with_group(channel)
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> MR19Receiver<C, P, E> {
+    /// Receive A from sender
+    pub fn setup_with_group(mut channel: C) -> OTResult<Self> {
+        let a = E::read(&mut channel)?;
+        Ok(Self {
+            channel,
+            a,
+            counter: 0,
+            encryption: Encryption::default(),
+            _encoding: PhantomData,
+        })
+    }
+
+    /// Set the encryption of the messages. The sender has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> OTReceiver for MR19Receiver<C, P, E> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let mut res = self.receive_batch::<N, T, R>(&[choice], rng)?;
+        Ok(res.remove(0))
+    }
+
+    /// Send (r_0, r_1) of all the OTs in one message, and receive all the ciphertexts at once.
+    fn receive_batch<const N: usize, T, R>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<T>>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        check_arity::<N>()?;
+        if let Some(&choice) = choices.iter().find(|&&c| c >= N) {
+            return Err(OTError::InvalidChoice(choice));
+        }
+
+        let b = P::generator();
+        let sks = choices
+            .iter()
+            .map(|&choice| {
+                // r_c = skB - H(r_{1-c}) where r_{1-c} is random
+                let sk = P::ScalarField::rand(rng);
+                let mut rs = [P::zero(); 2];
+                rs[1 - choice] = b * P::ScalarField::rand(rng);
+                rs[choice] = b * sk - hash_to_group::<P, E>(&rs[1 - choice])?;
+
+                E::write(&mut self.channel, &rs[0])?;
+                E::write(&mut self.channel, &rs[1])?;
+                Ok(sk)
+            })
+            .collect::<OTResult<Vec<_>>>()?;
+        self.channel.flush()?;
+
+        let mut bytes = [
+            vec![0u8; self.encryption.ciphertext_len::<T>()],
+            vec![0u8; self.encryption.ciphertext_len::<T>()],
+        ];
+        let result = choices
+            .iter()
+            .zip(sks)
+            .enumerate()
+            .map(|(j, (&choice, sk))| {
+                let key = key::<P, E>(self.counter + j, choice, &(self.a * sk))?;
+                self.channel.read_bytes(&mut bytes[0])?;
+                self.channel.read_bytes(&mut bytes[1])?;
+                self.encryption.decrypt(&bytes[choice], &key)
+            })
+            .collect::<OTResult<Vec<_>>>()?;
+        self.counter += choices.len();
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use ark_ec::Group;
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{channel::MemChannel, ot::encoding::Compressed};
+
+    fn run<P: CurveGroup, E: GroupEncoding<P> + 'static>(
+        choices: Vec<usize>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let n = choices.len();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_receiver = MR19Receiver::<_, P, E>::setup_with_group(receiver_channel)?;
+            // run twice to check that the keys of the batches are separated
+            let first = ot_receiver.receive_batch::<2, Block128, ThreadRng>(&choices, &mut rng)?;
+            let second = ot_receiver.receive_batch::<2, Block128, ThreadRng>(&choices, &mut rng)?;
+            Ok::<_, OTError>((choices, first, second))
+        });
+
+        let mut rng = thread_rng();
+        let mut ot_sender = MR19Sender::<_, P, E>::setup_with_group(sender_channel, &mut rng)?;
+        let values = (0..n as u128)
+            .map(|i| [Block128::from(2 * i), Block128::from(2 * i + 1)])
+            .collect::<Vec<_>>();
+        ot_sender.send_batch(&values)?;
+        ot_sender.send_batch(&values)?;
+
+        let (choices, first, second) = receiver_handle.join().unwrap()?;
+        for (((v, c), r0), r1) in values.iter().zip(choices).zip(first).zip(second) {
+            assert_eq!(v[c], r0);
+            assert_eq!(v[c], r1);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_mr_ot() -> Result<(), Box<dyn std::error::Error>> {
+        run::<G, Ristretto>(vec![0, 1, 1, 0])?;
+        run::<ark_bls12_381::G1Projective, Compressed>(vec![1, 0])?;
+        run::<ark_secp256k1::Projective, Compressed>(vec![0, 1])
+    }
+
+    #[test]
+    fn test_hash_to_group() {
+        let mut rng = thread_rng();
+        let p = G::generator() * Zp::rand(&mut rng);
+        let h = hash_to_group::<G, Ristretto>(&p).unwrap();
+        assert_eq!(h, hash_to_group::<G, Ristretto>(&p).unwrap());
+        assert!(Ristretto::decode(&Ristretto::encode(&h).unwrap()).is_ok());
+    }
+}
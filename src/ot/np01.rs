@@ -0,0 +1,252 @@
+//! This module implements 1-of-n oblivious transfer of Naor and Pinkas described in
+//! "Efficient Oblivious Transfer Protocols", SODA 2001, in the random oracle model.
+//!
+//! For each batch, the sender samples r and random points C_1, .., C_{n-1} and sends
+//! (rB, C_1, .., C_{n-1}). The receiver with choice c samples k, sets PK_c = kB and sends
+//! PK_0, where PK_i = C_i - PK_0 for i > 0. It can compute the discrete log of PK_c only.
+//! The sender encrypts m_i with H(j, i, r * PK_i), and the receiver decrypts m_c with
+//! H(j, c, k * rB).
+use std::marker::PhantomData;
+
+use ark_ec::CurveGroup;
+use ark_std::{rand::Rng, UniformRand};
+use sha3::{Digest, Keccak256};
+
+use crate::{block::*, channel::AbstractChannel, types::*};
+
+use super::{
+    auth::Encryption,
+    encoding::{GroupEncoding, Ristretto},
+    OTError, OTReceiver, OTResult, OTSender,
+};
+
+/// Key of i-th message of j-th OT in a batch
+fn key<P: CurveGroup, E: GroupEncoding<P>>(j: usize, i: usize, p: &P) -> OTResult<[u8; 32]> {
+    let mut hasher = Keccak256::default();
+    hasher.update((j as u64).to_le_bytes());
+    hasher.update((i as u64).to_le_bytes());
+    hasher.update(E::encode(p)?);
+    Ok(hasher.finalize().into())
+}
+
+/// Naor-Pinkas sender over the group P, whose points are sent in encoding E
+pub struct NP01Sender<C: AbstractChannel, P: CurveGroup = G, E: GroupEncoding<P> = Ristretto> {
+    pub channel: C,
+    encryption: Encryption,
+    _group: PhantomData<(P, E)>,
+}
+
+impl<C: AbstractChannel> NP01Sender<C> {
+    /// Create the sender over ed25519
+    pub fn new(channel: C) -> Self {
+        Self::new_with_group(channel)
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> NP01Sender<C, P, E> {
+    /// Create the sender over the group P
+    pub fn new_with_group(channel: C) -> Self {
+        Self {
+            channel,
+            encryption: Encryption::default(),
+            _group: PhantomData,
+        }
+    }
+
+    /// Set the encryption of the messages. The receiver has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+
+    /// Run the batch of OTs with the given randomness
+    fn send_batch_with<const N: usize, T: Block, R: Rng>(
+        &mut self,
+        values: &[[T; N]],
+        rng: &mut R,
+    ) -> OTResult<()> {
+        let b = P::generator();
+        let r = P::ScalarField::rand(rng);
+        let cs = (1..N)
+            .map(|_| b * P::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+
+        // Send rB and C_i to receiver
+        E::write(&mut self.channel, &(b * r))?;
+        for c in cs.iter() {
+            E::write(&mut self.channel, c)?;
+        }
+        self.channel.flush()?;
+
+        // Receive PK_0 of each OT
+        let pk0s = values
+            .iter()
+            .map(|_| E::read(&mut self.channel))
+            .collect::<OTResult<Vec<_>>>()?;
+
+        for (j, (pk0, vs)) in pk0s.into_iter().zip(values).enumerate() {
+            let pk0_r = pk0 * r;
+            for (i, v) in vs.iter().enumerate() {
+                // r * PK_i = r * C_i - r * PK_0
+                let pk_r = if i == 0 { pk0_r } else { cs[i - 1] * r - pk0_r };
+                let k = key::<P, E>(j, i, &pk_r)?;
+                self.channel.write_bytes(&self.encryption.encrypt(v, &k))?;
+            }
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> OTSender for NP01Sender<C, P, E> {
+    fn send<const N: usize, T: Block + Clone>(&mut self, values: [T; N]) -> OTResult<()> {
+        self.send_batch(&[values])
+    }
+
+    /// Sender parameters are shared with all the OTs in the batch
+    fn send_batch<const N: usize, T: Block + Clone>(&mut self, values: &[[T; N]]) -> OTResult<()> {
+        self.send_batch_with(values, &mut rand::thread_rng())
+    }
+}
+
+/// Naor-Pinkas receiver over the group P, whose points are sent in encoding E
+pub struct NP01Receiver<C: AbstractChannel, P: CurveGroup = G, E: GroupEncoding<P> = Ristretto> {
+    pub channel: C,
+    encryption: Encryption,
+    _group: PhantomData<(P, E)>,
+}
+
+impl<C: AbstractChannel> NP01Receiver<C> {
+    /// Create the receiver over ed25519
+    pub fn new(channel: C) -> Self {
+        Self::new_with_group(channel)
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> NP01Receiver<C, P, E> {
+    /// Create the receiver over the group P
+    pub fn new_with_group(channel: C) -> Self {
+        Self {
+            channel,
+            encryption: Encryption::default(),
+            _group: PhantomData,
+        }
+    }
+
+    /// Set the encryption of the messages. The sender has to use the same mode.
+    pub fn with_encryption(mut self, encryption: Encryption) -> Self {
+        self.encryption = encryption;
+        self
+    }
+}
+
+impl<C: AbstractChannel, P: CurveGroup, E: GroupEncoding<P>> OTReceiver for NP01Receiver<C, P, E> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let mut res = self.receive_batch::<N, T, R>(&[choice], rng)?;
+        Ok(res.remove(0))
+    }
+
+    fn receive_batch<const N: usize, T, R>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<T>>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        if let Some(&choice) = choices.iter().find(|&&c| c >= N) {
+            return Err(OTError::InvalidChoice(choice));
+        }
+
+        // Receive rB and C_i from sender
+        let b = P::generator();
+        let rb = E::read(&mut self.channel)?;
+        let cs = (1..N)
+            .map(|_| E::read(&mut self.channel))
+            .collect::<OTResult<Vec<_>>>()?;
+
+        // Set PK_c = kB and send PK_0
+        let ks = choices
+            .iter()
+            .map(|&choice| {
+                let k = P::ScalarField::rand(rng);
+                let pk0 = if choice == 0 {
+                    b * k
+                } else {
+                    cs[choice - 1] - b * k
+                };
+                E::write(&mut self.channel, &pk0)?;
+                Ok(k)
+            })
+            .collect::<OTResult<Vec<_>>>()?;
+        self.channel.flush()?;
+
+        let mut bytes = vec![0u8; self.encryption.ciphertext_len::<T>()];
+        choices
+            .iter()
+            .zip(ks)
+            .enumerate()
+            .map(|(j, (&choice, k))| {
+                let key = key::<P, E>(j, choice, &(rb * k))?;
+                let mut res = None;
+                for i in 0..N {
+                    self.channel.read_bytes(&mut bytes)?;
+                    if i == choice {
+                        res = Some(self.encryption.decrypt(&bytes, &key)?);
+                    }
+                }
+                res.ok_or(OTError::InvalidChoice(choice))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{channel::MemChannel, ot::encoding::Compressed};
+
+    fn run<const N: usize, P: CurveGroup, E: GroupEncoding<P> + 'static>(
+        choices: Vec<usize>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+        let n = choices.len();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut ot_receiver = NP01Receiver::<_, P, E>::new_with_group(receiver_channel);
+            let res = ot_receiver.receive_batch::<N, Block128, ThreadRng>(&choices, &mut rng)?;
+            Ok::<_, OTError>((choices, res))
+        });
+
+        let mut ot_sender = NP01Sender::<_, P, E>::new_with_group(sender_channel);
+        let values = (0..n)
+            .map(|i| std::array::from_fn(|j| Block128::from((N * i + j) as u128)))
+            .collect::<Vec<[Block128; N]>>();
+        ot_sender.send_batch(&values)?;
+
+        let (choices, result) = receiver_handle.join().unwrap()?;
+        for ((v, c), r) in values.iter().zip(choices).zip(result) {
+            assert_eq!(v[c], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_np_ot() -> Result<(), Box<dyn std::error::Error>> {
+        run::<2, G, Ristretto>(vec![0, 1, 1, 0])?;
+        run::<4, G, Ristretto>(vec![3, 0, 2, 1])?;
+        run::<2, ark_secp256k1::Projective, Compressed>(vec![1, 0])
+    }
+}
@@ -0,0 +1,242 @@
+//! Adapter turning 1-of-2 OT into 1-of-N OT, described in
+//! "Oblivious Transfer and Polynomial Evaluation" by Naor and Pinkas.
+//!
+//! For each OT, the sender samples l = ⌈log N⌉ key pairs (K_i^0, K_i^1) and sends
+//! m_x ^ F(K_1^{x_1}, x) ^ .. ^ F(K_l^{x_l}, x) for all x in 0..N, where x_i is i-th bit of x.
+//! The receiver gets K_i^{c_i} with l 1-of-2 OTs, which are enough to unmask m_c only.
+use ark_std::rand::Rng;
+use rand::thread_rng;
+use sha3::{Digest, Keccak256};
+
+use crate::{
+    block::{Block, Block128},
+    channel::AbstractChannel,
+};
+
+use super::{OTError, OTReceiver, OTResult, OTSender};
+
+/// Number of 1-of-2 OTs needed for 1-of-n OT
+fn log_n(n: usize) -> OTResult<usize> {
+    if n < 2 {
+        return Err(OTError::InvalidParameter(format!(
+            "1-of-N OT requires N >= 2, got {n}"
+        )));
+    }
+    Ok(n.next_power_of_two().trailing_zeros() as usize)
+}
+
+/// XOR the mask ⊕_i F(K_i^{x_i}, x) into the message.
+/// F expands the key to the length of the message with Keccak in counter mode.
+fn apply_mask(keys: &[Block128], x: usize, msg: &mut [u8]) {
+    for key in keys {
+        for (chunk_idx, chunk) in msg.chunks_mut(32).enumerate() {
+            let mut hasher = Keccak256::default();
+            hasher.update(key.as_bytes());
+            hasher.update((x as u64).to_le_bytes());
+            hasher.update((chunk_idx as u64).to_le_bytes());
+            let pad = hasher.finalize();
+            chunk.iter_mut().zip(pad).for_each(|(c, p)| *c ^= p);
+        }
+    }
+}
+
+/// 1-of-N OT sender built on top of 1-of-2 OT sender
+pub struct OneOfNSender<S: OTSender, C: AbstractChannel> {
+    ot: S,
+    channel: C,
+}
+
+impl<S: OTSender, C: AbstractChannel> OneOfNSender<S, C> {
+    pub fn new(ot: S, channel: C) -> Self {
+        Self { ot, channel }
+    }
+}
+
+impl<S: OTSender, C: AbstractChannel> OTSender for OneOfNSender<S, C> {
+    fn send<const N: usize, T: Block + Clone>(&mut self, values: [T; N]) -> OTResult<()> {
+        self.send_batch(&[values])
+    }
+
+    /// Run l 1-of-2 OTs of all the OTs in one batch, then send all the masked values
+    fn send_batch<const N: usize, T: Block + Clone>(&mut self, values: &[[T; N]]) -> OTResult<()> {
+        let l = log_n(N)?;
+
+        let mut rng = thread_rng();
+        let keys = (0..values.len() * l)
+            .map(|_| {
+                [
+                    Block128::from(rng.gen::<u128>()),
+                    Block128::from(rng.gen::<u128>()),
+                ]
+            })
+            .collect::<Vec<_>>();
+        self.ot.send_batch(&keys)?;
+
+        for (vs, ks) in values.iter().zip(keys.chunks(l)) {
+            for (x, v) in vs.iter().enumerate() {
+                let x_keys = ks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, k)| k[(x >> i) & 1])
+                    .collect::<Vec<_>>();
+                let mut bytes = v.as_bytes();
+                apply_mask(&x_keys, x, &mut bytes);
+                self.channel.write_bytes(&bytes)?;
+            }
+        }
+        self.channel.flush()?;
+
+        Ok(())
+    }
+}
+
+/// 1-of-N OT receiver built on top of 1-of-2 OT receiver
+pub struct OneOfNReceiver<R: OTReceiver, C: AbstractChannel> {
+    ot: R,
+    channel: C,
+}
+
+impl<R: OTReceiver, C: AbstractChannel> OneOfNReceiver<R, C> {
+    pub fn new(ot: R, channel: C) -> Self {
+        Self { ot, channel }
+    }
+}
+
+impl<Recv: OTReceiver, C: AbstractChannel> OTReceiver for OneOfNReceiver<Recv, C> {
+    fn receive<const N: usize, T, R>(&mut self, choice: usize, rng: &mut R) -> OTResult<T>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let mut res = self.receive_batch::<N, T, R>(&[choice], rng)?;
+        Ok(res.remove(0))
+    }
+
+    fn receive_batch<const N: usize, T, R>(
+        &mut self,
+        choices: &[usize],
+        rng: &mut R,
+    ) -> OTResult<Vec<T>>
+    where
+        T: Block + Clone + Default,
+        R: Rng,
+    {
+        let l = log_n(N)?;
+        if let Some(&choice) = choices.iter().find(|&&c| c >= N) {
+            return Err(OTError::InvalidChoice(choice));
+        }
+
+        // receive K_i^{c_i} for each bit of the choices
+        let bits = choices
+            .iter()
+            .flat_map(|&c| (0..l).map(move |i| (c >> i) & 1))
+            .collect::<Vec<_>>();
+        let keys = self.ot.receive_batch::<2, Block128, R>(&bits, rng)?;
+
+        let mut bytes = vec![0u8; T::default().bytes_len()];
+        choices
+            .iter()
+            .zip(keys.chunks(l))
+            .map(|(&choice, ks)| {
+                let mut res = None;
+                for x in 0..N {
+                    self.channel.read_bytes(&mut bytes)?;
+                    if x == choice {
+                        apply_mask(ks, x, &mut bytes);
+                        res = Some(T::from_bytes(&bytes));
+                    }
+                }
+                res.ok_or(OTError::InvalidChoice(choice))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{
+        block::Block256,
+        channel::MemChannel,
+        ot::{
+            co15::{CO15Receiver, CO15Sender},
+            kos15::{Kos15Receiver, Kos15Sender},
+        },
+    };
+
+    const CHOICES: [usize; 6] = [0, 4, 2, 1, 3, 4];
+
+    fn values() -> Vec<[Block256; 5]> {
+        (0..CHOICES.len())
+            .map(|j| std::array::from_fn(|x| Block256::from([(5 * j + x) as u8; 32])))
+            .collect()
+    }
+
+    #[test]
+    fn test_one_of_n_co15() -> Result<(), Box<dyn std::error::Error>> {
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let ot = CO15Receiver::setup(ot_receiver_channel)?;
+            let mut receiver = OneOfNReceiver::new(ot, receiver_channel);
+            receiver.receive_batch::<5, Block256, ThreadRng>(&CHOICES, &mut rng)
+        });
+
+        let mut rng = thread_rng();
+        let ot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+        let mut sender = OneOfNSender::new(ot, sender_channel);
+        let values = values();
+        sender.send_batch(&values)?;
+
+        let received = receiver_handle.join().unwrap()?;
+        for ((v, c), r) in values.iter().zip(CHOICES).zip(received) {
+            assert_eq!(v[c], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_one_of_n_extension() -> Result<(), Box<dyn std::error::Error>> {
+        let (base_sender_channel, base_receiver_channel) = MemChannel::pair();
+        let (ext_sender_channel, ext_receiver_channel) = MemChannel::pair();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        // KOS15 extension on top of CO15 base OTs
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut base_ot = CO15Sender::setup(base_sender_channel, &mut rng)?;
+            let ot = Kos15Receiver::setup(&mut base_ot, ext_receiver_channel, &mut rng)?;
+            let mut receiver = OneOfNReceiver::new(ot, receiver_channel);
+            receiver.receive_batch::<5, Block256, ThreadRng>(&CHOICES, &mut rng)
+        });
+
+        let mut rng = thread_rng();
+        let mut base_ot = CO15Receiver::setup(base_receiver_channel)?;
+        let ot = Kos15Sender::setup(&mut base_ot, ext_sender_channel, &mut rng)?;
+        let mut sender = OneOfNSender::new(ot, sender_channel);
+        let values = values();
+        sender.send_batch(&values)?;
+
+        let received = receiver_handle.join().unwrap()?;
+        for ((v, c), r) in values.iter().zip(CHOICES).zip(received) {
+            assert_eq!(v[c], r);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_log_n() {
+        assert_eq!(log_n(2).unwrap(), 1);
+        assert_eq!(log_n(5).unwrap(), 3);
+        assert_eq!(log_n(256).unwrap(), 8);
+        assert!(log_n(1).is_err());
+    }
+}
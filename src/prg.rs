@@ -0,0 +1,99 @@
+//! AES-128 counter mode pseudo random generator
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use aes::Aes128;
+use rand::{CryptoRng, Error as RandError, RngCore};
+
+use crate::block::{Block, Block128};
+
+/// Expands 128 bit seed into arbitrary length pseudo random stream.
+/// The state is kept between calls so that the same seed can be used to generate fresh output.
+#[derive(Clone)]
+pub struct Prg {
+    cipher: Aes128,
+    counter: u128,
+    buff: [u8; 16],
+    // position of next unused byte in buff
+    pos: usize,
+}
+
+impl Prg {
+    pub fn new(seed: Block128) -> Self {
+        let key = GenericArray::clone_from_slice(&seed.as_bytes());
+        Self {
+            cipher: Aes128::new(&key),
+            counter: 0,
+            buff: [0u8; 16],
+            pos: 16,
+        }
+    }
+
+    /// Generate next 128 bit block
+    pub fn gen_block(&mut self) -> Block128 {
+        let mut bytes = [0u8; 16];
+        self.fill_bytes(&mut bytes);
+        Block128::from(bytes)
+    }
+
+    fn refill(&mut self) {
+        let mut block = GenericArray::from(self.counter.to_le_bytes());
+        self.cipher.encrypt_block(&mut block);
+        self.buff = block.into();
+        self.counter += 1;
+        self.pos = 0;
+    }
+}
+
+impl RngCore for Prg {
+    fn next_u32(&mut self) -> u32 {
+        let mut bytes = [0u8; 4];
+        self.fill_bytes(&mut bytes);
+        u32::from_le_bytes(bytes)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut bytes = [0u8; 8];
+        self.fill_bytes(&mut bytes);
+        u64::from_le_bytes(bytes)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        let mut written = 0;
+        while written < dest.len() {
+            if self.pos == 16 {
+                self.refill();
+            }
+            let n = (16 - self.pos).min(dest.len() - written);
+            dest[written..written + n].copy_from_slice(&self.buff[self.pos..self.pos + n]);
+            self.pos += n;
+            written += n;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Prg {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prg_deterministic() {
+        let seed = Block128::from(42);
+        let mut prg_0 = Prg::new(seed);
+        let mut prg_1 = Prg::new(seed);
+
+        let mut bytes_0 = [0u8; 37];
+        let mut bytes_1 = [0u8; 37];
+        prg_0.fill_bytes(&mut bytes_0[..5]);
+        prg_0.fill_bytes(&mut bytes_0[5..]);
+        prg_1.fill_bytes(&mut bytes_1);
+
+        assert_eq!(bytes_0, bytes_1);
+        assert_ne!(prg_0.gen_block(), Prg::new(Block128::from(43)).gen_block());
+    }
+}
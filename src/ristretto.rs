@@ -0,0 +1,182 @@
+//! Ristretto255 encoding of ed25519 points described in RFC 9496.
+//!
+//! ed25519 has cofactor 8. Ristretto maps the points of the even subgroup to classes modulo
+//! the 4-torsion subgroup, which gives a group of prime order with a canonical 32 byte
+//! encoding. The curve arithmetic is still done on `G`: two points represent the same
+//! Ristretto element iff they have the same encoding.
+use ark_ec::twisted_edwards::TECurveConfig;
+use ark_ed25519::{EdwardsConfig, Fq};
+use ark_ff::{BigInteger, BigInteger256, Field, MontFp, One, PrimeField, Zero};
+
+use crate::types::G;
+
+/// Length of the encoding in bytes
+pub const ENCODED_LEN: usize = 32;
+
+const SQRT_M1: Fq =
+    MontFp!("19681161376707505956807079304988542015446066515923890162744021073123829784752");
+const INVSQRT_A_MINUS_D: Fq =
+    MontFp!("54469307008909316920995813868745141605393597292927456921205312896311721017578");
+
+/// (p - 5) / 8 = 2^252 - 3
+const P_MINUS_5_DIV_8: [u64; 4] = [
+    0xffff_ffff_ffff_fffd,
+    0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff,
+    0x0fff_ffff_ffff_ffff,
+];
+
+/// Field element is negative if the least significant bit of its canonical encoding is set
+fn is_negative(x: &Fq) -> bool {
+    x.into_bigint().is_odd()
+}
+
+fn abs(x: Fq) -> Fq {
+    if is_negative(&x) {
+        -x
+    } else {
+        x
+    }
+}
+
+/// Returns (true, sqrt(u / v)) if u / v is square, otherwise (false, sqrt(i * u / v)).
+/// The root is always non negative.
+fn sqrt_ratio_m1(u: Fq, v: Fq) -> (bool, Fq) {
+    let v3 = v.square() * v;
+    let v7 = v3.square() * v;
+    let mut r = (u * v3) * (u * v7).pow(P_MINUS_5_DIV_8);
+    let check = v * r.square();
+
+    let correct_sign_sqrt = check == u;
+    let flipped_sign_sqrt = check == -u;
+    let flipped_sign_sqrt_i = check == -u * SQRT_M1;
+
+    if flipped_sign_sqrt || flipped_sign_sqrt_i {
+        r *= SQRT_M1;
+    }
+    (correct_sign_sqrt || flipped_sign_sqrt, abs(r))
+}
+
+/// Encode the Ristretto element represented by the point
+pub fn encode(p: &G) -> [u8; ENCODED_LEN] {
+    let (x0, y0, z0, t0) = (p.x, p.y, p.z, p.t);
+
+    let u1 = (z0 + y0) * (z0 - y0);
+    let u2 = x0 * y0;
+    let (_, invsqrt) = sqrt_ratio_m1(Fq::one(), u1 * u2.square());
+    let den1 = invsqrt * u1;
+    let den2 = invsqrt * u2;
+    let z_inv = den1 * den2 * t0;
+
+    let rotate = is_negative(&(t0 * z_inv));
+    let (x, mut y, den_inv) = if rotate {
+        (y0 * SQRT_M1, x0 * SQRT_M1, den1 * INVSQRT_A_MINUS_D)
+    } else {
+        (x0, y0, den2)
+    };
+    if is_negative(&(x * z_inv)) {
+        y = -y;
+    }
+    let s = abs(den_inv * (z0 - y));
+
+    let mut bytes = [0u8; ENCODED_LEN];
+    bytes.copy_from_slice(&s.into_bigint().to_bytes_le());
+    bytes
+}
+
+/// Decode a canonical encoding. Returns `None` if the bytes are not a valid encoding.
+pub fn decode(bytes: &[u8; ENCODED_LEN]) -> Option<G> {
+    // s has to be canonical and non negative
+    let limbs =
+        std::array::from_fn(|i| u64::from_le_bytes(std::array::from_fn(|j| bytes[8 * i + j])));
+    let s = Fq::from_bigint(BigInteger256::new(limbs))?;
+    if is_negative(&s) {
+        return None;
+    }
+
+    let ss = s.square();
+    let u1 = Fq::one() - ss;
+    let u2 = Fq::one() + ss;
+    let u2_sqr = u2.square();
+    let v = -(EdwardsConfig::COEFF_D * u1.square()) - u2_sqr;
+
+    let (was_square, invsqrt) = sqrt_ratio_m1(Fq::one(), v * u2_sqr);
+    let den_x = invsqrt * u2;
+    let den_y = invsqrt * den_x * v;
+
+    let x = abs((s + s) * den_x);
+    let y = u1 * den_y;
+    let t = x * y;
+    if !was_square || is_negative(&t) || y.is_zero() {
+        return None;
+    }
+
+    Some(G::new_unchecked(x, y, t, Fq::one()))
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::Group;
+    use ark_std::UniformRand;
+    use rand::prelude::thread_rng;
+
+    use super::*;
+    use crate::types::Zp;
+
+    fn from_hex(s: &str) -> [u8; ENCODED_LEN] {
+        std::array::from_fn(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+    }
+
+    #[test]
+    fn test_generator_multiples() {
+        // Test vectors from RFC 9496 Appendix A.1
+        let vectors = [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "e2f2ae0a6abc4e71a884a961c500515f58e30b6aa582dd8db6a65945e08d2d76",
+            "6a493210f7499cd17fecb510ae0cea23a110e8d5b901f8acadd3095c73a3b919",
+            "94741f5d5d52755ece4f23f044ee27d5d1ea1e2bd196b462166b16152a9d0259",
+            "da80862773358b466ffadfe0b3293ab3d9fd53c5ea6c955358f568322daf6a57",
+        ];
+
+        let mut p = G::zero();
+        for v in vectors {
+            let expected = from_hex(v);
+            assert_eq!(encode(&p), expected);
+
+            let decoded = decode(&expected).unwrap();
+            assert_eq!(encode(&decoded), expected);
+            p += G::generator();
+        }
+    }
+
+    #[test]
+    fn test_invalid_encodings() {
+        // Test vectors from RFC 9496 Appendix A.2
+        let vectors = [
+            // non-canonical field encodings
+            "00ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+            "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // negative field elements
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "01ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+            // non-square x^2
+            "26948d35ca62e643e26a83177332e6b6afeb9d08e4268b650f1f5bbd8d81d371",
+            // t is negative
+            "f6e18ba48aaa2c4d9ec8c9da43963cf2aefc7b7c8a264f7a99dcf86ca2e6b212",
+        ];
+        for v in vectors {
+            assert!(decode(&from_hex(v)).is_none());
+        }
+    }
+
+    #[test]
+    fn test_torsion_invariance() {
+        let mut rng = thread_rng();
+        let p = G::generator() * Zp::rand(&mut rng);
+
+        // (0, -1) is the point of order 2, which is in the 4-torsion subgroup
+        let torsion = G::new_unchecked(Fq::zero(), -Fq::one(), Fq::zero(), Fq::one());
+        assert_eq!(encode(&p), encode(&(p + torsion)));
+        assert_eq!(encode(&decode(&encode(&p)).unwrap()), encode(&p));
+    }
+}
@@ -0,0 +1,721 @@
+//! Persistent storage of precomputed correlations.
+//!
+//! A store file holds the correlations of one party of one kind, so that random OTs, COTs and
+//! VOLE tuples generated in setup can be used by a later process.
+//!
+//! The file starts with a header
+//!
+//! | magic | version | kind | item length | count | used | global |
+//! |-------|---------|------|-------------|-------|------|--------|
+//! | 4     | u16 LE  | u8   | u32 LE      | u64 LE | u64 LE | kind specific |
+//!
+//! followed by `count` items of fixed length. `used` counts the items already handed out.
+//! It is written and synced to the disk before the items are returned, so a correlation is never
+//! returned twice even if the process crashes and opens the store again.
+//! Both parties must consume their stores in the same order.
+use std::{
+    fs::File,
+    io::{Error as IoError, Read, Seek, SeekFrom, Write},
+    marker::PhantomData,
+    path::Path,
+};
+
+use ark_ed25519::Fr;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::rand::Rng;
+
+use crate::{
+    block::{Block, Block128, Block256},
+    channel::AbstractChannel,
+    ot::{
+        check_arity, rot_key, COTReceiver, COTSender, OTError, OTReceiver, OTSender, ROTReceiver,
+        ROTSender,
+    },
+    types::Zp,
+    vole::{
+        ot_vole::{VoleReceiver, VoleSender},
+        VoleError,
+    },
+};
+
+const MAGIC: [u8; 4] = *b"VZKC";
+
+/// Version of the file format
+pub const VERSION: u16 = 1;
+
+// offset of the used counter in the header
+const USED_OFFSET: u64 = 4 + 2 + 1 + 4 + 8;
+const HEADER_LEN: u64 = USED_OFFSET + 8;
+
+#[derive(thiserror::Error, Debug)]
+pub enum StoreError {
+    #[error(transparent)]
+    Io {
+        #[from]
+        source: IoError,
+    },
+    #[error(transparent)]
+    Serialize {
+        #[from]
+        source: SerializationError,
+    },
+    #[error(transparent)]
+    OT {
+        #[from]
+        source: OTError,
+    },
+    #[error(transparent)]
+    Vole {
+        #[from]
+        source: VoleError,
+    },
+    #[error("invalid store file: {0}")]
+    InvalidFormat(String),
+    #[error("unsupported store version {0}")]
+    UnsupportedVersion(u16),
+    #[error("{requested} correlations requested, but only {remaining} remain")]
+    Exhausted { requested: usize, remaining: usize },
+}
+
+pub type StoreResult<T> = Result<T, StoreError>;
+
+/// Fixed length binary encoding of the stored values
+pub trait Encode: Sized {
+    const LEN: usize;
+
+    fn encode(&self, out: &mut Vec<u8>);
+
+    /// Decode from exactly `LEN` bytes
+    fn decode(bytes: &[u8]) -> StoreResult<Self>;
+}
+
+impl Encode for () {
+    const LEN: usize = 0;
+
+    fn encode(&self, _out: &mut Vec<u8>) {}
+
+    fn decode(_bytes: &[u8]) -> StoreResult<Self> {
+        Ok(())
+    }
+}
+
+impl Encode for bool {
+    const LEN: usize = 1;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self as u8);
+    }
+
+    fn decode(bytes: &[u8]) -> StoreResult<Self> {
+        match bytes[0] {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(StoreError::InvalidFormat(format!("invalid bit {b}"))),
+        }
+    }
+}
+
+impl Encode for [u8; 32] {
+    const LEN: usize = 32;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> StoreResult<Self> {
+        Ok(std::array::from_fn(|i| bytes[i]))
+    }
+}
+
+impl Encode for Block128 {
+    const LEN: usize = 16;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> StoreResult<Self> {
+        Ok(Block128::from_bytes(bytes))
+    }
+}
+
+// implemented on Fr, which is Zp, since impls on the projection Zp overlap with the others
+impl Encode for Fr {
+    const LEN: usize = 32;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        // serialization into a vector never fails
+        let _ = self.serialize_compressed(out);
+    }
+
+    /// Non-canonical encodings are rejected
+    fn decode(bytes: &[u8]) -> StoreResult<Self> {
+        Ok(Fr::deserialize_compressed(bytes)?)
+    }
+}
+
+impl<A: Encode, B: Encode> Encode for (A, B) {
+    const LEN: usize = A::LEN + B::LEN;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> StoreResult<Self> {
+        Ok((A::decode(&bytes[..A::LEN])?, B::decode(&bytes[A::LEN..])?))
+    }
+}
+
+impl<T: Encode> Encode for [T; 2] {
+    const LEN: usize = 2 * T::LEN;
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        self[0].encode(out);
+        self[1].encode(out);
+    }
+
+    fn decode(bytes: &[u8]) -> StoreResult<Self> {
+        Ok([T::decode(&bytes[..T::LEN])?, T::decode(&bytes[T::LEN..])?])
+    }
+}
+
+/// Kind of correlations held by a store
+pub trait Kind {
+    /// Tag of the kind in the header
+    const TAG: u8;
+
+    /// Value shared with all the correlations, such as Δ
+    type Global: Encode;
+
+    type Item: Encode;
+}
+
+/// Random OT keys (k_0, k_1) of the sender
+pub struct RotSenderKind;
+
+impl Kind for RotSenderKind {
+    const TAG: u8 = 1;
+    type Global = ();
+    type Item = [[u8; 32]; 2];
+}
+
+/// Random choice c and key k_c of the random OT receiver
+pub struct RotReceiverKind;
+
+impl Kind for RotReceiverKind {
+    const TAG: u8 = 2;
+    type Global = ();
+    type Item = (bool, [u8; 32]);
+}
+
+/// Global Δ and keys k_i of the correlated OT sender
+pub struct CotSenderKind;
+
+impl Kind for CotSenderKind {
+    const TAG: u8 = 3;
+    type Global = Block128;
+    type Item = Block128;
+}
+
+/// Random choices b_i and k_i ^ b_i * Δ of the correlated OT receiver
+pub struct CotReceiverKind;
+
+impl Kind for CotReceiverKind {
+    const TAG: u8 = 4;
+    type Global = ();
+    type Item = (bool, Block128);
+}
+
+/// Global Δ and keys k_i of the VOLE sender
+pub struct VoleSenderKind;
+
+impl Kind for VoleSenderKind {
+    const TAG: u8 = 5;
+    type Global = Zp;
+    type Item = Zp;
+}
+
+/// Pairs (x_i, y_i) of the VOLE receiver, where y_i = k_i + x_i * Δ
+pub struct VoleReceiverKind;
+
+impl Kind for VoleReceiverKind {
+    const TAG: u8 = 6;
+    type Global = ();
+    type Item = (Zp, Zp);
+}
+
+/// Correlations of the kind K stored in a file
+pub struct CorrelationStore<K: Kind> {
+    file: File,
+    global: K::Global,
+    count: u64,
+    used: u64,
+    _kind: PhantomData<K>,
+}
+
+impl<K: Kind> CorrelationStore<K> {
+    /// Create a new store file with the correlations.
+    /// An existing file is never overwritten.
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        global: K::Global,
+        items: &[K::Item],
+    ) -> StoreResult<Self> {
+        let mut buff =
+            Vec::with_capacity(HEADER_LEN as usize + K::Global::LEN + items.len() * K::Item::LEN);
+        buff.extend_from_slice(&MAGIC);
+        buff.extend_from_slice(&VERSION.to_le_bytes());
+        buff.push(K::TAG);
+        buff.extend_from_slice(&(K::Item::LEN as u32).to_le_bytes());
+        buff.extend_from_slice(&(items.len() as u64).to_le_bytes());
+        buff.extend_from_slice(&0u64.to_le_bytes());
+        global.encode(&mut buff);
+        for item in items {
+            item.encode(&mut buff);
+        }
+
+        let mut file = File::options()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        file.write_all(&buff)?;
+        file.sync_all()?;
+
+        Ok(Self {
+            file,
+            global,
+            count: items.len() as u64,
+            used: 0,
+            _kind: PhantomData,
+        })
+    }
+
+    /// Open a store file created for the kind K
+    pub fn open<P: AsRef<Path>>(path: P) -> StoreResult<Self> {
+        let mut file = File::options().read(true).write(true).open(path)?;
+
+        let mut header = [0u8; HEADER_LEN as usize];
+        file.read_exact(&mut header)?;
+        if header[..4] != MAGIC {
+            return Err(StoreError::InvalidFormat("not a correlation store".into()));
+        }
+        let version = u16::from_le_bytes([header[4], header[5]]);
+        if version != VERSION {
+            return Err(StoreError::UnsupportedVersion(version));
+        }
+        if header[6] != K::TAG {
+            return Err(StoreError::InvalidFormat(format!(
+                "expected kind {}, got {}",
+                K::TAG,
+                header[6]
+            )));
+        }
+        let item_len = u32::from_le_bytes(std::array::from_fn(|i| header[7 + i]));
+        if item_len as usize != K::Item::LEN {
+            return Err(StoreError::InvalidFormat(format!(
+                "expected item length {}, got {item_len}",
+                K::Item::LEN
+            )));
+        }
+        let count = u64::from_le_bytes(std::array::from_fn(|i| header[11 + i]));
+        let used = u64::from_le_bytes(std::array::from_fn(|i| header[19 + i]));
+        if used > count {
+            return Err(StoreError::InvalidFormat(format!(
+                "{used} of {count} correlations used"
+            )));
+        }
+
+        let file_len = count
+            .checked_mul(K::Item::LEN as u64)
+            .and_then(|len| len.checked_add(HEADER_LEN + K::Global::LEN as u64))
+            .ok_or_else(|| StoreError::InvalidFormat(format!("{count} correlations")))?;
+        if file.metadata()?.len() != file_len {
+            return Err(StoreError::InvalidFormat("unexpected file length".into()));
+        }
+
+        let mut global = vec![0u8; K::Global::LEN];
+        file.read_exact(&mut global)?;
+        let global = K::Global::decode(&global)?;
+
+        Ok(Self {
+            file,
+            global,
+            count,
+            used,
+            _kind: PhantomData,
+        })
+    }
+
+    /// Value shared with all the correlations
+    pub fn global(&self) -> &K::Global {
+        &self.global
+    }
+
+    /// Number of correlations not used yet
+    pub fn remaining(&self) -> usize {
+        (self.count - self.used) as usize
+    }
+
+    /// Take the next n correlations.
+    /// They are marked as used on the disk before being returned.
+    pub fn take(&mut self, n: usize) -> StoreResult<Vec<K::Item>> {
+        if n > self.remaining() {
+            return Err(StoreError::Exhausted {
+                requested: n,
+                remaining: self.remaining(),
+            });
+        }
+
+        let start = self.used;
+        self.used += n as u64;
+        self.file.seek(SeekFrom::Start(USED_OFFSET))?;
+        self.file.write_all(&self.used.to_le_bytes())?;
+        self.file.sync_data()?;
+
+        let offset = HEADER_LEN + K::Global::LEN as u64 + start * K::Item::LEN as u64;
+        let mut bytes = vec![0u8; n * K::Item::LEN];
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(&mut bytes)?;
+
+        bytes.chunks(K::Item::LEN).map(K::Item::decode).collect()
+    }
+}
+
+impl CorrelationStore<RotSenderKind> {
+    /// Run n random OTs and store the keys of the sender
+    pub fn precompute<P: AsRef<Path>, S: ROTSender>(
+        path: P,
+        rot: &mut S,
+        n: usize,
+    ) -> StoreResult<Self> {
+        let items = (0..n)
+            .map(|_| Ok(rot.send_random::<2, Block256>()?.map(rot_key)))
+            .collect::<Result<Vec<_>, OTError>>()?;
+        Self::create(path, (), &items)
+    }
+}
+
+impl CorrelationStore<RotReceiverKind> {
+    /// Run n random OTs with random choices and store them
+    pub fn precompute<P: AsRef<Path>, Rot: ROTReceiver, R: Rng>(
+        path: P,
+        rot: &mut Rot,
+        n: usize,
+        rng: &mut R,
+    ) -> StoreResult<Self> {
+        let items = (0..n)
+            .map(|_| {
+                let c = rng.gen::<bool>();
+                let k = rot.receive_random::<2, Block256, R>(c as usize, rng)?;
+                Ok((c, rot_key(k)))
+            })
+            .collect::<Result<Vec<_>, OTError>>()?;
+        Self::create(path, (), &items)
+    }
+}
+
+impl CorrelationStore<CotSenderKind> {
+    /// Run n correlated OTs and store Δ and the keys
+    pub fn precompute<P: AsRef<Path>, S: COTSender>(
+        path: P,
+        cot: &mut S,
+        n: usize,
+    ) -> StoreResult<Self> {
+        let keys = cot.send_correlated(n)?;
+        Self::create(path, cot.delta(), &keys)
+    }
+}
+
+impl CorrelationStore<CotReceiverKind> {
+    /// Run n correlated OTs with random choices and store them
+    pub fn precompute<P: AsRef<Path>, Cot: COTReceiver, R: Rng>(
+        path: P,
+        cot: &mut Cot,
+        n: usize,
+        rng: &mut R,
+    ) -> StoreResult<Self> {
+        let choices = (0..n).map(|_| rng.gen::<bool>()).collect::<Vec<_>>();
+        let blocks = cot.receive_correlated(&choices)?;
+        let items = choices.into_iter().zip(blocks).collect::<Vec<_>>();
+        Self::create(path, (), &items)
+    }
+}
+
+impl CorrelationStore<VoleSenderKind> {
+    /// Generate n random VOLE correlations and store Δ and the keys
+    pub fn precompute<P, C, S, R>(
+        path: P,
+        vole: &mut VoleSender<C, S>,
+        n: usize,
+        rng: &mut R,
+    ) -> StoreResult<Self>
+    where
+        P: AsRef<Path>,
+        C: AbstractChannel,
+        S: OTSender,
+        R: Rng,
+    {
+        let keys = vole.send(n, rng)?;
+        Self::create(path, vole.delta(), &keys)
+    }
+}
+
+impl CorrelationStore<VoleReceiverKind> {
+    /// Generate n random VOLE correlations and store them
+    pub fn precompute<P, C, Recv, R>(
+        path: P,
+        vole: &mut VoleReceiver<C, Recv>,
+        n: usize,
+        rng: &mut R,
+    ) -> StoreResult<Self>
+    where
+        P: AsRef<Path>,
+        C: AbstractChannel,
+        Recv: OTReceiver,
+        R: Rng,
+    {
+        let pairs = vole.receive(n, rng)?;
+        Self::create(path, (), &pairs)
+    }
+}
+
+/// Random OT sender consuming the stored random OTs.
+/// The stored choices are corrected to the requested ones as in KOS15.
+pub struct StoredRotSender<C: AbstractChannel> {
+    store: CorrelationStore<RotSenderKind>,
+    channel: C,
+}
+
+impl<C: AbstractChannel> StoredRotSender<C> {
+    pub fn new(store: CorrelationStore<RotSenderKind>, channel: C) -> Self {
+        Self { store, channel }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.store.remaining()
+    }
+}
+
+impl<C: AbstractChannel> ROTSender for StoredRotSender<C> {
+    /// Receive the choice correction d and return the keys in the order of the choice
+    fn send_random<const N: usize, T: Block>(&mut self) -> Result<[T; N], OTError> {
+        check_arity::<N>()?;
+        let keys = self.store.take(1)?.remove(0);
+        let mut d = [0u8; 1];
+        self.channel.read_bytes(&mut d)?;
+        let d = match d[0] {
+            0 | 1 => d[0] as usize,
+            b => return Err(OTError::MalformedMessage(format!("choice correction {b}"))),
+        };
+        Ok(std::array::from_fn(|i| T::from_bytes(&keys[i ^ d])))
+    }
+}
+
+/// Random OT receiver consuming the stored random OTs
+pub struct StoredRotReceiver<C: AbstractChannel> {
+    store: CorrelationStore<RotReceiverKind>,
+    channel: C,
+}
+
+impl<C: AbstractChannel> StoredRotReceiver<C> {
+    pub fn new(store: CorrelationStore<RotReceiverKind>, channel: C) -> Self {
+        Self { store, channel }
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.store.remaining()
+    }
+}
+
+impl<C: AbstractChannel> ROTReceiver for StoredRotReceiver<C> {
+    /// Send the choice correction d = choice ^ c and return the stored key
+    fn receive_random<const N: usize, T: Block, R: Rng>(
+        &mut self,
+        choice: usize,
+        _rng: &mut R,
+    ) -> Result<T, OTError> {
+        check_arity::<N>()?;
+        if choice >= N {
+            return Err(OTError::InvalidChoice(choice));
+        }
+        let (c, key) = self.store.take(1)?.remove(0);
+        self.channel.write_bytes(&[(choice ^ c as usize) as u8])?;
+        self.channel.flush()?;
+        Ok(T::from_bytes(&key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{path::PathBuf, thread};
+
+    use rand::prelude::{thread_rng, ThreadRng};
+
+    use super::*;
+    use crate::{
+        channel::MemChannel,
+        ot::co15::{CO15Receiver, CO15Sender},
+    };
+
+    /// Unique path in the temporary directory, removed first if it exists
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("volezk-{}-{name}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_rot_store() -> Result<(), Box<dyn std::error::Error>> {
+        const N: usize = 8;
+        let sender_path = temp_path("rot-sender");
+        let receiver_path = temp_path("rot-receiver");
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        let path = receiver_path.clone();
+        let receiver_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let mut rot = CO15Receiver::setup(ot_receiver_channel)?;
+            drop(CorrelationStore::<RotReceiverKind>::precompute(
+                &path, &mut rot, N, &mut rng,
+            )?);
+
+            // use half of the store, then open it again
+            let mut receiver =
+                StoredRotReceiver::new(CorrelationStore::open(&path)?, receiver_channel);
+            let mut keys = (0..N / 2)
+                .map(|j| receiver.receive_random::<2, Block128, ThreadRng>(j % 2, &mut rng))
+                .collect::<Result<Vec<_>, OTError>>()?;
+            let store = CorrelationStore::open(&path)?;
+            assert_eq!(store.remaining(), N / 2);
+            receiver.store = store;
+            for j in N / 2..N {
+                keys.push(receiver.receive_random::<2, Block128, ThreadRng>(j % 2, &mut rng)?);
+            }
+            Ok::<_, StoreError>(keys)
+        });
+
+        let mut rng = thread_rng();
+        let mut rot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+        drop(CorrelationStore::<RotSenderKind>::precompute(
+            &sender_path,
+            &mut rot,
+            N,
+        )?);
+
+        let mut sender =
+            StoredRotSender::new(CorrelationStore::open(&sender_path)?, sender_channel);
+        let keys = (0..N)
+            .map(|_| sender.send_random::<2, Block128>())
+            .collect::<Result<Vec<_>, OTError>>()?;
+        assert_eq!(sender.remaining(), 0);
+        assert!(matches!(
+            sender.store.take(1),
+            Err(StoreError::Exhausted { .. })
+        ));
+        match sender.send_random::<2, Block128>() {
+            Err(OTError::Store { source }) => {
+                assert!(matches!(*source, StoreError::Exhausted { .. }))
+            }
+            _ => panic!("expected the store to be exhausted"),
+        }
+
+        let received = receiver_handle.join().unwrap()?;
+        for (j, (k, r)) in keys.iter().zip(received).enumerate() {
+            assert_eq!(k[j % 2], r);
+        }
+
+        std::fs::remove_file(sender_path)?;
+        std::fs::remove_file(receiver_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_vole_store() -> Result<(), Box<dyn std::error::Error>> {
+        const N: usize = 3;
+        let sender_path = temp_path("vole-sender");
+        let receiver_path = temp_path("vole-receiver");
+        let (ot_sender_channel, ot_receiver_channel) = MemChannel::pair();
+        let (sender_channel, receiver_channel) = MemChannel::pair();
+
+        let path = sender_path.clone();
+        let sender_handle = thread::spawn(move || {
+            let mut rng = thread_rng();
+            let ot = CO15Sender::setup(ot_sender_channel, &mut rng)?;
+            let mut vole = VoleSender::new(ot, sender_channel, &mut rng);
+            CorrelationStore::<VoleSenderKind>::precompute(&path, &mut vole, N, &mut rng)?;
+            Ok::<_, StoreError>(())
+        });
+
+        let mut rng = thread_rng();
+        let ot = CO15Receiver::setup(ot_receiver_channel)?;
+        let mut vole = VoleReceiver::new(ot, receiver_channel);
+        CorrelationStore::<VoleReceiverKind>::precompute(&receiver_path, &mut vole, N, &mut rng)?;
+        sender_handle.join().unwrap()?;
+
+        let mut sender_store = CorrelationStore::<VoleSenderKind>::open(&sender_path)?;
+        let mut receiver_store = CorrelationStore::<VoleReceiverKind>::open(&receiver_path)?;
+        let delta = *sender_store.global();
+        let keys = sender_store.take(N)?;
+        let pairs = receiver_store.take(N)?;
+        for (k, (x, y)) in keys.into_iter().zip(pairs) {
+            assert_eq!(y, k + x * delta);
+        }
+
+        // the store is not overwritten
+        assert!(CorrelationStore::<VoleSenderKind>::create(&sender_path, delta, &[]).is_err());
+
+        std::fs::remove_file(sender_path)?;
+        std::fs::remove_file(receiver_path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_store() -> Result<(), Box<dyn std::error::Error>> {
+        let path = temp_path("invalid");
+        let delta = Block128::from(7);
+        let keys = [Block128::from(1), Block128::from(2)];
+        drop(CorrelationStore::<CotSenderKind>::create(
+            &path, delta, &keys,
+        )?);
+
+        let mut store = CorrelationStore::<CotSenderKind>::open(&path)?;
+        assert_eq!(*store.global(), delta);
+        assert_eq!(store.take(2)?, keys);
+
+        assert!(matches!(
+            CorrelationStore::<CotReceiverKind>::open(&path),
+            Err(StoreError::InvalidFormat(_))
+        ));
+
+        let mut bytes = std::fs::read(&path)?;
+        bytes[4] = 2;
+        std::fs::write(&path, &bytes)?;
+        assert!(matches!(
+            CorrelationStore::<CotSenderKind>::open(&path),
+            Err(StoreError::UnsupportedVersion(2))
+        ));
+
+        // count overflows the file length
+        bytes[4] = 1;
+        bytes[11..19].copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &bytes)?;
+        assert!(matches!(
+            CorrelationStore::<CotSenderKind>::open(&path),
+            Err(StoreError::InvalidFormat(_))
+        ));
+
+        bytes[11..19].copy_from_slice(&2u64.to_le_bytes());
+        bytes.pop();
+        std::fs::write(&path, &bytes)?;
+        assert!(matches!(
+            CorrelationStore::<CotSenderKind>::open(&path),
+            Err(StoreError::InvalidFormat(_))
+        ));
+
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -1 +1,20 @@
-mod ot_vole;
+//! Implement vector oblivious linear evaluation
+use crate::{channel::ChannelError, ot::OTError};
+
+pub mod ot_vole;
+
+#[derive(thiserror::Error, Debug)]
+pub enum VoleError {
+    #[error(transparent)]
+    OT {
+        #[from]
+        source: OTError,
+    },
+    #[error(transparent)]
+    Channel {
+        #[from]
+        source: ChannelError,
+    },
+}
+
+pub type VoleResult<T> = Result<T, VoleError>;
@@ -1,94 +1,189 @@
 //! This module implement VOLE from oblivious transfer and learning parity with noise assumption.
+//!
+//! Random VOLE is generated with Gilboa's multiplication.
+//! For each bit x_i of the receiver's x, the parties run 1-of-2 OT on random seeds (s_0, s_1).
+//! The sender sends correction c_i = H(s_0) - H(s_1) + 2^i * Δ so that the receiver can compute
+//! H(s_{x_i}) + x_i * c_i = H(s_0) + x_i * 2^i * Δ.
+//! Summing over all the bits gives y = k + x * Δ where k = Σ H(s_0).
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_std::{rand::Rng, UniformRand};
+use sha3::{Digest, Keccak256};
 
 use crate::{
+    block::{Block, Block128},
     ot::{OTReceiver, OTSender},
+    types::Zp,
     AbstractChannel,
 };
 
+use super::VoleResult;
+
+/// Number of OTs to perform for each correlation.
+const BITS: usize = Zp::MODULUS_BIT_SIZE as usize;
+
+/// Hash random OT seed into Zp
+fn hash_to_zp(seed: &Block128) -> Zp {
+    let mut hasher = Keccak256::default();
+    hasher.update(seed.as_bytes());
+    Zp::from_le_bytes_mod_order(&hasher.finalize())
+}
+
 /// Vole sender inputs delta and outputs k
 /// where y = k + x * Δ
-struct VoleSender<C: AbstractChannel, Sender: OTSender> {
+pub struct VoleSender<C: AbstractChannel, Sender: OTSender> {
     ot_sender: Sender,
     channel: C,
+    delta: Zp,
 }
 
 impl<C: AbstractChannel, Sender: OTSender> VoleSender<C, Sender> {
-    fn new(ot_sender: Sender, channel: C) -> Self {
-        Self { channel, ot_sender }
+    /// Create new vole sender with randomly sampled global Δ
+    pub fn new<R: Rng>(ot_sender: Sender, channel: C, rng: &mut R) -> Self {
+        let delta = Zp::rand(rng);
+        Self {
+            channel,
+            ot_sender,
+            delta,
+        }
     }
 
-    /// Random vole send
-    fn send(&mut self) {
-        todo!()
+    /// Global Δ shared with all the correlations generated by this sender
+    pub fn delta(&self) -> Zp {
+        self.delta
+    }
+
+    /// Random vole send.
+    /// Returns n keys k_i such that receiver's y_i = k_i + x_i * Δ
+    pub fn send<R: Rng>(&mut self, n: usize, rng: &mut R) -> VoleResult<Vec<Zp>> {
+        let mut keys = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            let mut k = Zp::ZERO;
+            let mut corrections = Vec::with_capacity(BITS);
+            // 2^i * Δ
+            let mut pow_delta = self.delta;
+
+            let seeds = (0..BITS)
+                .map(|_| {
+                    [
+                        Block128::from(rng.gen::<u128>()),
+                        Block128::from(rng.gen::<u128>()),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            self.ot_sender.send_batch(&seeds)?;
+
+            for [s0, s1] in seeds.iter() {
+                let r0 = hash_to_zp(s0);
+                let r1 = hash_to_zp(s1);
+                corrections.push(r0 - r1 + pow_delta);
+
+                k += r0;
+                pow_delta.double_in_place();
+            }
+
+            // send corrections to receiver
+            for c in corrections {
+                self.channel.write_zp(c)?;
+            }
+            self.channel.flush()?;
+
+            keys.push(k);
+        }
+
+        Ok(keys)
     }
 }
 
 /// Vole receiver inputs x and outputs y
 /// where y = k + x * Δ
-struct VoleReceiver<C: AbstractChannel, Receiver: OTReceiver> {
+pub struct VoleReceiver<C: AbstractChannel, Receiver: OTReceiver> {
     ot_receiver: Receiver,
     channel: C,
 }
 
 impl<C: AbstractChannel, Receiver: OTReceiver> VoleReceiver<C, Receiver> {
-    fn new(ot_receiver: Receiver, channel: C) -> Self {
+    pub fn new(ot_receiver: Receiver, channel: C) -> Self {
         Self {
             channel,
             ot_receiver,
         }
     }
 
-    fn receive(&mut self) {
-        todo!()
+    /// Random vole receive.
+    /// Samples n random x_i and returns pairs of (x_i, y_i) such that y_i = k_i + x_i * Δ
+    pub fn receive<R: Rng>(&mut self, n: usize, rng: &mut R) -> VoleResult<Vec<(Zp, Zp)>> {
+        let mut result = Vec::with_capacity(n);
+
+        for _ in 0..n {
+            let x = Zp::rand(rng);
+            let x_bits = x.into_bigint().to_bits_le();
+
+            let choices = x_bits
+                .iter()
+                .take(BITS)
+                .map(|&b| b as usize)
+                .collect::<Vec<_>>();
+            let seeds = self
+                .ot_receiver
+                .receive_batch::<2, Block128, R>(&choices, rng)?;
+
+            let mut y = Zp::ZERO;
+            for (seed, &b) in seeds.iter().zip(&x_bits) {
+                let c = self.channel.read_zp()?;
+                y += hash_to_zp(seed);
+                if b {
+                    y += c;
+                }
+            }
+
+            result.push((x, y));
+        }
+
+        Ok(result)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use rand::prelude::thread_rng;
-    use std::{
-        io::{BufReader, BufWriter},
-        os::unix::net::UnixStream,
-        thread,
-    };
+    use std::thread;
 
     use super::*;
-    use crate::{channel::Channel, ot::co15::*};
+    use crate::{
+        channel::{MemChannel, MuxChannels},
+        ot::co15::*,
+    };
+
+    const N: usize = 2;
 
     #[test]
     fn test_ole() {
-        let (sender_stream, receiver_stream) = UnixStream::pair().unwrap();
-        let (ot_sender_stream, ot_receiver_stream) = UnixStream::pair().unwrap();
+        // base OT on stream 0 and VOLE on stream 1 of one connection
+        let (sender_channel, receiver_channel) = MemChannel::pair();
 
         let sender_thread = thread::spawn(|| {
             let mut rng = thread_rng();
+            let mux = MuxChannels::new(sender_channel);
+            let ot_sender = CO15Sender::setup(mux.channel(0), &mut rng).unwrap();
 
-            let reader = BufReader::new(sender_stream.try_clone().unwrap());
-            let writer = BufWriter::new(sender_stream);
-            let sender_chan = Channel::new(reader, writer);
-
-            let ot_reader = BufReader::new(ot_sender_stream.try_clone().unwrap());
-            let ot_writer = BufWriter::new(ot_sender_stream);
-            let ot_sender_channel = Channel::new(ot_reader, ot_writer);
-            let ot_sender = CO15Sender::setup(ot_sender_channel, &mut rng).unwrap();
-
-            let mut alice = VoleSender::new(ot_sender, sender_chan);
-            alice.send()
+            let mut alice = VoleSender::new(ot_sender, mux.channel(1), &mut rng);
+            let keys = alice.send(N, &mut rng).unwrap();
+            (alice.delta(), keys)
         });
 
-        let reader = BufReader::new(receiver_stream.try_clone().unwrap());
-        let writer = BufWriter::new(receiver_stream);
-        let receiver_chan = Channel::new(reader, writer);
+        let mut rng = thread_rng();
+        let mux = MuxChannels::new(receiver_channel);
+        let ot_receiver = CO15Receiver::setup(mux.channel(0)).unwrap();
 
-        let ot_reader = BufReader::new(ot_receiver_stream.try_clone().unwrap());
-        let ot_writer = BufWriter::new(ot_receiver_stream);
-        let ot_receiver_channel = Channel::new(ot_reader, ot_writer);
-        let ot_receiver = CO15Receiver::setup(ot_receiver_channel).unwrap();
+        let mut bob = VoleReceiver::new(ot_receiver, mux.channel(1));
+        let received = bob.receive(N, &mut rng).unwrap();
 
-        let mut bob = VoleReceiver::new(ot_receiver, receiver_chan);
-        bob.receive();
-
-        // follower things to do
-        let sender_result = sender_thread.join().unwrap();
+        let (delta, keys) = sender_thread.join().unwrap();
+        assert_eq!(keys.len(), N);
+        assert_eq!(received.len(), N);
+        for (k, (x, y)) in keys.iter().zip(received) {
+            assert_eq!(y, *k + x * delta);
+        }
     }
 }